@@ -0,0 +1,65 @@
+//! times the hand-written parser against the old pest grammar on the tour files both accept.
+//! pest only builds its pairs here, the old parser still had to turn them into the AST, so its
+//! time is a lower bound for the old front end
+
+#[path = "../tests/old_grammar/mod.rs"]
+mod old_grammar;
+
+use rename_me::parser::Parser;
+use std::{
+    fs,
+    hint::black_box,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+const COPIES: usize = 200;
+const RUNS: usize = 10;
+
+/// the fastest of `RUNS` runs of `parse`
+fn time(mut parse: impl FnMut() -> bool) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            assert!(black_box(parse()));
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let tour = Path::new(env!("CARGO_MANIFEST_DIR")).join("tour");
+    let mut paths: Vec<_> = fs::read_dir(&tour)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "idk"))
+        .collect();
+    paths.sort();
+
+    let sources: Vec<_> = paths
+        .iter()
+        .map(|path| fs::read_to_string(path).unwrap())
+        .filter(|src| old_grammar::accepts(src) && Parser::parse(src).is_ok())
+        .collect();
+    assert!(
+        !sources.is_empty(),
+        "no tour file is accepted by both parsers"
+    );
+
+    let src = sources.join("\n").repeat(COPIES);
+    let new = time(|| Parser::parse(&src).is_ok());
+    let old = time(|| old_grammar::accepts(&src));
+
+    println!(
+        "{} tour files repeated {COPIES} times, {} KiB",
+        sources.len(),
+        src.len() / 1024
+    );
+    println!("hand-written: {new:?}");
+    println!("pest:         {old:?}");
+    println!(
+        "the hand-written parser is {:.1}x faster",
+        old.as_secs_f64() / new.as_secs_f64()
+    );
+}
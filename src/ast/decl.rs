@@ -1,18 +1,24 @@
 use crate::{
-    ast::{
-        assert_exhausted, match_next, unreachable_grammar, Alias, AstBuildResult, AstNode, Block,
-        Expr,
-    },
-    parser::Rule,
+    ast::{match_next, Alias, AstBuildResult, AstNode, Block, Expr},
+    lexer::TokenKind,
+    parser::Parser,
+    span::Span,
+    syntax::NodeKind,
 };
-use pest::iterators::{Pair, Pairs};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct DocComment {
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
 pub struct Bounds {
     pub expr: Expr,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum AliasEval {
     Let,
     Var,
@@ -20,100 +26,280 @@ pub enum AliasEval {
     Type,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AliasDecl {
+    pub doc: Option<DocComment>,
     pub eval: AliasEval,
     pub alias: Alias,
     pub bounds: Option<Bounds>,
     pub rhs: Expr,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FnDecl {
+    pub doc: Option<DocComment>,
     pub alias: Alias,
     pub bounds: Option<Bounds>,
     pub body: Block,
+    pub span: Span,
+    /// the type a method is declared on, `T` in `fn T::name`
+    pub owner: Option<Alias>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct IfaceAliasItem {
+    pub doc: Option<DocComment>,
+    pub eval: AliasEval,
+    pub alias: Alias,
+    pub bounds: Option<Bounds>,
+    pub default: Option<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct IfaceFnItem {
+    pub doc: Option<DocComment>,
+    pub alias: Alias,
+    pub bounds: Option<Bounds>,
+    pub default: Option<Block>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum IfaceItem {
+    Alias(IfaceAliasItem),
+    Fn(IfaceFnItem),
+}
+
+#[derive(Debug, Clone)]
+pub struct IfaceDecl {
+    pub doc: Option<DocComment>,
+    pub alias: Alias,
+    pub items: Vec<IfaceItem>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
 pub enum Decl {
     Alias(AliasDecl),
     Fn(FnDecl),
+    Iface(IfaceDecl),
+}
+
+impl IfaceItem {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Alias(item) => item.span,
+            Self::Fn(item) => item.span,
+        }
+    }
+}
+
+impl Decl {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Alias(decl) => decl.span,
+            Self::Fn(decl) => decl.span,
+            Self::Iface(decl) => decl.span,
+        }
+    }
+}
+
+impl AstNode for DocComment {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let mut lines = Vec::new();
+        while let Some(token) = parser.next_if(TokenKind::DocComment) {
+            let text = &parser.text(&token)[2..];
+            lines.push(text.strip_prefix(' ').unwrap_or(text));
+        }
+
+        if lines.is_empty() {
+            return Ok(None);
+        }
+
+        let span = parser.finish_node(cp, NodeKind::DocComment);
+        Ok(Some(Self {
+            text: lines.join("\n"),
+            span,
+        }))
+    }
 }
 
 impl AstNode for Bounds {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::bounds).into_inner();
-        let expr = Expr::expect(&mut pairs)?;
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        match_next!(parser, TokenKind::Colon);
+        let expr = Expr::expect(parser)?;
 
-        assert_exhausted!(pairs, Self);
-        Ok(Some(Self { expr }))
+        let span = parser.finish_node(cp, NodeKind::Bounds);
+        Ok(Some(Self { expr, span }))
     }
 }
 
 impl AstNode for AliasEval {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::alias_eval).into_inner();
-        let eval = match pairs.next().as_ref().map(Pair::as_rule) {
-            Some(Rule::kw_let) => Self::Let,
-            Some(Rule::kw_var) => Self::Var,
-            Some(Rule::kw_const) => Self::Const,
-            Some(Rule::kw_type) => Self::Type,
-            _ => unreachable_grammar!(Self),
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let eval = match parser.peek() {
+            Some(TokenKind::KwLet) => Self::Let,
+            Some(TokenKind::KwVar) => Self::Var,
+            Some(TokenKind::KwConst) => Self::Const,
+            Some(TokenKind::KwType) => Self::Type,
+            _ => return Ok(None),
         };
 
-        assert_exhausted!(pairs, Self);
+        parser.bump();
         Ok(Some(eval))
     }
 }
 
 impl AstNode for AliasDecl {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::alias_decl).into_inner();
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let doc = DocComment::parse(parser)?;
+        let eval = AliasEval::expect(parser)?;
+        let alias = Alias::expect(parser)?;
+        let bounds = Bounds::parse(parser)?;
 
-        let eval = AliasEval::expect(&mut pairs)?;
-        let alias = Alias::expect(&mut pairs)?;
-        let bounds = Bounds::parse(&mut pairs)?;
-        let rhs = Expr::expect(&mut pairs)?;
+        parser.expect(TokenKind::Eq)?;
+        let rhs = Expr::expect(parser)?;
 
-        assert_exhausted!(pairs, Self);
+        let span = parser.finish_node(cp, NodeKind::AliasDecl);
         Ok(Some(Self {
+            doc,
             eval,
             alias,
             bounds,
             rhs,
+            span,
         }))
     }
 }
 
 impl AstNode for FnDecl {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::fn_decl).into_inner();
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let doc = DocComment::parse(parser)?;
+        parser.expect(TokenKind::KwFn)?;
 
-        let alias = Alias::expect(&mut pairs)?;
-        let bounds = Bounds::parse(&mut pairs)?;
-        let body = Block::expect(&mut pairs)?;
+        let mut alias = Alias::expect(parser)?;
+        let mut owner = None;
+        if parser.next_if(TokenKind::ColonColon).is_some() {
+            owner = Some(std::mem::replace(&mut alias, Alias::expect(parser)?));
+        }
 
-        assert_exhausted!(pairs, Self);
+        let bounds = Bounds::parse(parser)?;
+        let body = Block::expect(parser)?;
+
+        let span = parser.finish_node(cp, NodeKind::FnDecl);
         Ok(Some(Self {
+            doc,
             alias,
             bounds,
             body,
+            span,
+            owner,
         }))
     }
 }
 
-impl AstNode for Decl {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::decl).into_inner();
+impl AstNode for IfaceAliasItem {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let doc = DocComment::parse(parser)?;
+        let eval = AliasEval::expect(parser)?;
+        let alias = Alias::expect(parser)?;
+        let bounds = Bounds::parse(parser)?;
+
+        let default = match parser.next_if(TokenKind::Eq) {
+            Some(_) => Some(Expr::expect(parser)?),
+            None => None,
+        };
 
-        let decl = match pairs.peek().as_ref().map(Pair::as_rule) {
-            Some(Rule::alias_decl) => AliasDecl::expect(&mut pairs).map(Self::Alias),
-            Some(Rule::fn_decl) => FnDecl::expect(&mut pairs).map(Self::Fn),
-            _ => unreachable_grammar!(Self),
+        let span = parser.finish_node(cp, NodeKind::IfaceAliasItem);
+        Ok(Some(Self {
+            doc,
+            eval,
+            alias,
+            bounds,
+            default,
+            span,
+        }))
+    }
+}
+
+impl AstNode for IfaceFnItem {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let doc = DocComment::parse(parser)?;
+        parser.expect(TokenKind::KwFn)?;
+
+        let alias = Alias::expect(parser)?;
+        let bounds = Bounds::parse(parser)?;
+        let default = Block::parse(parser)?;
+
+        let span = parser.finish_node(cp, NodeKind::IfaceFnItem);
+        Ok(Some(Self {
+            doc,
+            alias,
+            bounds,
+            default,
+            span,
+        }))
+    }
+}
+
+impl AstNode for IfaceItem {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let item = match parser.peek_after_docs() {
+            Some(TokenKind::KwLet | TokenKind::KwVar | TokenKind::KwConst | TokenKind::KwType) => {
+                IfaceAliasItem::expect(parser).map(Self::Alias)
+            }
+            Some(TokenKind::KwFn) => IfaceFnItem::expect(parser).map(Self::Fn),
+            _ => return Ok(None),
+        };
+
+        item.map(Into::into)
+    }
+}
+
+impl AstNode for IfaceDecl {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let doc = DocComment::parse(parser)?;
+        parser.expect(TokenKind::KwIface)?;
+
+        let alias = Alias::expect(parser)?;
+        parser.expect(TokenKind::LBrace)?;
+
+        let mut items = Vec::new();
+        while let Some(item) = IfaceItem::parse(parser)? {
+            parser.next_if(TokenKind::Semi);
+            items.push(item);
+        }
+
+        parser.expect(TokenKind::RBrace)?;
+        let span = parser.finish_node(cp, NodeKind::IfaceDecl);
+        Ok(Some(Self {
+            doc,
+            alias,
+            items,
+            span,
+        }))
+    }
+}
+
+impl AstNode for Decl {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let decl = match parser.peek_after_docs() {
+            Some(TokenKind::KwLet | TokenKind::KwVar | TokenKind::KwConst | TokenKind::KwType) => {
+                AliasDecl::expect(parser).map(Self::Alias)
+            }
+            Some(TokenKind::KwFn) => FnDecl::expect(parser).map(Self::Fn),
+            Some(TokenKind::KwIface) => IfaceDecl::expect(parser).map(Self::Iface),
+            _ => return Ok(None),
         };
 
-        assert_exhausted!(pairs, Self);
         decl.map(Into::into)
     }
 }
@@ -0,0 +1,450 @@
+//! serialisations of the AST for external tools. both formats describe the same tree:
+//!
+//! - every node has a `kind`, the name of its rust type, and a `span`, the byte range
+//!   `[start, end)` it covers in the source. `Decl`, `Expr`, `Stmt` and `IfaceItem` are not
+//!   nodes, they are replaced by the node they hold
+//! - the other fields of a node keep the names of the rust fields, in declaration order. absent
+//!   optional fields are `null` in JSON and left out of S-expressions
+//! - enums without data are lowercase symbols, e.g. `let`, `pub_get` or `fn_arrow`
+//! - doc comments are strings and bounds are the expression they hold
+//!
+//! JSON:         `{"kind": "Alias", "span": [4, 5], "alias": "a"}`
+//! S-expression: `(Alias 4..5 :alias "a")`
+//!
+//! the root is a list of the top level decls. fields are never renamed or reordered, new fields
+//! are only appended
+
+use crate::{
+    ast::{
+        AliasDecl, AliasEval, BinOp, Block, Bounds, CtrlOp, Decl, DocComment, Expr, FieldVis,
+        IfStmt, IfaceItem, InterpolatedStrPart, PostOp, Stmt, StructField, WhileStmt,
+    },
+    span::Span,
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone)]
+pub enum DumpValue {
+    Null,
+    Bool(bool),
+    Int(u64),
+    Float(f64),
+    Str(String),
+    Sym(&'static str),
+    List(Vec<DumpValue>),
+    Node(DumpNode),
+}
+
+#[derive(Debug, Clone)]
+pub struct DumpNode {
+    pub kind: &'static str,
+    pub span: Span,
+    pub fields: Vec<(&'static str, DumpValue)>,
+}
+
+impl DumpNode {
+    fn new(kind: &'static str, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            fields: Vec::new(),
+        }
+    }
+
+    fn field(mut self, name: &'static str, val: impl Into<DumpValue>) -> Self {
+        self.fields.push((name, val.into()));
+        self
+    }
+}
+
+impl From<DumpNode> for DumpValue {
+    fn from(value: DumpNode) -> Self {
+        Self::Node(value)
+    }
+}
+
+impl<T: Into<DumpValue>> From<Option<T>> for DumpValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl<T: Into<DumpValue>> From<Vec<T>> for DumpValue {
+    fn from(value: Vec<T>) -> Self {
+        Self::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+pub trait Dump {
+    fn dump(&self) -> DumpValue;
+}
+
+impl<T: Dump> Dump for [T] {
+    fn dump(&self) -> DumpValue {
+        DumpValue::List(self.iter().map(Dump::dump).collect())
+    }
+}
+
+fn dump_opt<T: Dump>(node: &Option<T>) -> DumpValue {
+    node.as_ref().map_or(DumpValue::Null, Dump::dump)
+}
+
+fn doc(doc: &Option<DocComment>) -> DumpValue {
+    doc.as_ref()
+        .map_or(DumpValue::Null, |doc| DumpValue::Str(doc.text.clone()))
+}
+
+fn bounds(bounds: &Option<Bounds>) -> DumpValue {
+    bounds
+        .as_ref()
+        .map_or(DumpValue::Null, |bounds| bounds.expr.dump())
+}
+
+fn eval(eval: &AliasEval) -> DumpValue {
+    DumpValue::Sym(match eval {
+        AliasEval::Let => "let",
+        AliasEval::Var => "var",
+        AliasEval::Const => "const",
+        AliasEval::Type => "type",
+    })
+}
+
+fn vis(vis: &Option<FieldVis>) -> DumpValue {
+    match vis {
+        Some(FieldVis::Pub) => DumpValue::Sym("pub"),
+        Some(FieldVis::PubGet) => DumpValue::Sym("pub_get"),
+        None => DumpValue::Null,
+    }
+}
+
+impl Dump for AliasDecl {
+    fn dump(&self) -> DumpValue {
+        DumpNode::new("AliasDecl", self.span)
+            .field("doc", doc(&self.doc))
+            .field("eval", eval(&self.eval))
+            .field("alias", self.alias.alias.clone())
+            .field("bounds", bounds(&self.bounds))
+            .field("rhs", self.rhs.dump())
+            .into()
+    }
+}
+
+impl Dump for Decl {
+    fn dump(&self) -> DumpValue {
+        match self {
+            Self::Alias(decl) => decl.dump(),
+            Self::Fn(decl) => DumpNode::new("FnDecl", decl.span)
+                .field("doc", doc(&decl.doc))
+                .field("alias", decl.alias.alias.clone())
+                .field("bounds", bounds(&decl.bounds))
+                .field("body", decl.body.dump())
+                .field(
+                    "owner",
+                    decl.owner.as_ref().map(|owner| owner.alias.clone()),
+                )
+                .into(),
+            Self::Iface(decl) => DumpNode::new("IfaceDecl", decl.span)
+                .field("doc", doc(&decl.doc))
+                .field("alias", decl.alias.alias.clone())
+                .field("items", decl.items.dump())
+                .into(),
+        }
+    }
+}
+
+impl Dump for IfaceItem {
+    fn dump(&self) -> DumpValue {
+        match self {
+            Self::Alias(item) => DumpNode::new("IfaceAliasItem", item.span)
+                .field("doc", doc(&item.doc))
+                .field("eval", eval(&item.eval))
+                .field("alias", item.alias.alias.clone())
+                .field("bounds", bounds(&item.bounds))
+                .field("default", dump_opt(&item.default))
+                .into(),
+            Self::Fn(item) => DumpNode::new("IfaceFnItem", item.span)
+                .field("doc", doc(&item.doc))
+                .field("alias", item.alias.alias.clone())
+                .field("bounds", bounds(&item.bounds))
+                .field("default", dump_opt(&item.default))
+                .into(),
+        }
+    }
+}
+
+impl Dump for Block {
+    fn dump(&self) -> DumpValue {
+        DumpNode::new("Block", self.span)
+            .field("stmts", self.stmts.dump())
+            .field(
+                "expr",
+                self.expr.as_deref().map_or(DumpValue::Null, Dump::dump),
+            )
+            .into()
+    }
+}
+
+impl Dump for StructField {
+    fn dump(&self) -> DumpValue {
+        DumpNode::new("StructField", self.span)
+            .field("doc", doc(&self.doc))
+            .field("vis", vis(&self.vis))
+            .field("alias", self.alias.alias.clone())
+            .field("bounds", bounds(&self.bounds))
+            .field("val", dump_opt(&self.val))
+            .into()
+    }
+}
+
+impl Dump for Expr {
+    fn dump(&self) -> DumpValue {
+        let node = match self {
+            Self::Alias(alias) => {
+                DumpNode::new("Alias", alias.span).field("alias", alias.alias.clone())
+            }
+            Self::Block(block) => return block.dump(),
+            Self::Struct(expr) => DumpNode::new("StructExpr", expr.span)
+                .field("default_vis", vis(&expr.default_vis))
+                .field("fields", expr.fields.dump()),
+
+            Self::Bool(lit) => DumpNode::new("BoolLit", lit.span).field("val", lit.val),
+            Self::Int(lit) => DumpNode::new("IntLit", lit.span)
+                .field("neg", lit.neg)
+                .field("val", lit.val),
+            Self::Float(lit) => DumpNode::new("FloatLit", lit.span).field("val", lit.val),
+            Self::Chr(lit) => DumpNode::new("ChrLit", lit.span).field("val", lit.val.to_string()),
+            Self::Str(lit) => DumpNode::new("StrLit", lit.span).field("val", lit.val.clone()),
+            Self::InterpolatedStr(lit) => {
+                let parts = lit
+                    .parts
+                    .iter()
+                    .map(|part| match part {
+                        InterpolatedStrPart::Str(val) => DumpValue::Str(val.clone()),
+                        InterpolatedStrPart::Expr(expr) => expr.dump(),
+                    })
+                    .collect::<Vec<_>>();
+
+                DumpNode::new("InterpolatedStr", lit.span).field("parts", parts)
+            }
+
+            Self::IfStmt(stmt) => return stmt.dump(),
+            Self::WhileStmt(stmt) => return stmt.dump(),
+
+            Self::UnaryExpr { expr, op, span } => DumpNode::new("UnaryExpr", *span)
+                .field("expr", expr.dump())
+                .field(
+                    "op",
+                    DumpValue::Sym(match op {
+                        PostOp::Ref => "ref",
+                        PostOp::Deref => "deref",
+                        PostOp::Unwrap => "unwrap",
+                    }),
+                ),
+            Self::BinaryExpr { lhs, rhs, op, span } => DumpNode::new("BinaryExpr", *span)
+                .field("lhs", lhs.dump())
+                .field("rhs", rhs.dump())
+                .field(
+                    "op",
+                    DumpValue::Sym(match op {
+                        BinOp::EvalPath => "eval_path",
+                        BinOp::ExecPath => "exec_path",
+                        BinOp::FnArrow => "fn_arrow",
+                        BinOp::PipeR => "pipe_r",
+                        BinOp::PipeL => "pipe_l",
+                    }),
+                ),
+        };
+
+        node.into()
+    }
+}
+
+impl Dump for IfStmt {
+    fn dump(&self) -> DumpValue {
+        DumpNode::new("IfStmt", self.span)
+            .field("lhs", self.lhs.dump())
+            .field("rhs", self.rhs.dump())
+            .field("chain", dump_opt(&self.chain))
+            .into()
+    }
+}
+
+impl Dump for WhileStmt {
+    fn dump(&self) -> DumpValue {
+        DumpNode::new("WhileStmt", self.span)
+            .field("lhs", self.lhs.dump())
+            .field("rhs", self.rhs.dump())
+            .into()
+    }
+}
+
+impl Dump for Stmt {
+    fn dump(&self) -> DumpValue {
+        match self {
+            Self::Assign(stmt) => DumpNode::new("AssignStmt", stmt.span)
+                .field("lhs", stmt.lhs.dump())
+                .field("rhs", stmt.rhs.dump())
+                .into(),
+            Self::Ctrl(stmt) => DumpNode::new("CtrlStmt", stmt.span)
+                .field(
+                    "op",
+                    DumpValue::Sym(match stmt.op {
+                        CtrlOp::Return => "return",
+                        CtrlOp::Defer => "defer",
+                        CtrlOp::Continue => "continue",
+                        CtrlOp::Break => "break",
+                    }),
+                )
+                .field("rhs", dump_opt(&stmt.rhs))
+                .into(),
+            Self::If(stmt) => stmt.dump(),
+            Self::While(stmt) => stmt.dump(),
+
+            Self::Decl(decl) => decl.dump(),
+        }
+    }
+}
+
+impl From<bool> for DumpValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<u64> for DumpValue {
+    fn from(value: u64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f64> for DumpValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<String> for DumpValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+/// writes `val` as a quoted JSON string
+pub(crate) fn write_str(out: &mut String, val: &str) {
+    out.push('"');
+    for ch in val.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if ch.is_control() => write!(out, "\\u{:04x}", ch as u32).unwrap(),
+            ch => out.push(ch),
+        }
+    }
+
+    out.push('"');
+}
+
+/// floats that JSON can't represent are written as the strings "inf", "-inf" and "nan"
+fn write_float(out: &mut String, val: f64) {
+    match val.is_finite() {
+        true => write!(out, "{val:?}").unwrap(),
+        false => write_str(out, &val.to_string().to_lowercase()),
+    }
+}
+
+impl DumpValue {
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        let mut out = String::new();
+        self.write_sexpr(&mut out);
+        out
+    }
+
+    fn write_json(&self, out: &mut String) {
+        match self {
+            Self::Null => out.push_str("null"),
+            Self::Bool(val) => write!(out, "{val}").unwrap(),
+            Self::Int(val) => write!(out, "{val}").unwrap(),
+            Self::Float(val) => write_float(out, *val),
+            Self::Str(val) => write_str(out, val),
+            Self::Sym(val) => write_str(out, val),
+            Self::List(vals) => {
+                out.push('[');
+                for (i, val) in vals.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+
+                    val.write_json(out);
+                }
+
+                out.push(']');
+            }
+            Self::Node(node) => {
+                out.push_str("{\"kind\":");
+                write_str(out, node.kind);
+                write!(out, ",\"span\":[{},{}]", node.span.start, node.span.end).unwrap();
+
+                for (name, val) in &node.fields {
+                    write!(out, ",\"{name}\":").unwrap();
+                    val.write_json(out);
+                }
+
+                out.push('}');
+            }
+        }
+    }
+
+    fn write_sexpr(&self, out: &mut String) {
+        match self {
+            Self::Null => out.push_str("nil"),
+            Self::Bool(val) => write!(out, "{val}").unwrap(),
+            Self::Int(val) => write!(out, "{val}").unwrap(),
+            Self::Float(val) => write_float(out, *val),
+            Self::Str(val) => write_str(out, val),
+            Self::Sym(val) => out.push_str(val),
+            Self::List(vals) => {
+                out.push('(');
+                for (i, val) in vals.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+
+                    val.write_sexpr(out);
+                }
+
+                out.push(')');
+            }
+            Self::Node(node) => {
+                write!(out, "({} {}..{}", node.kind, node.span.start, node.span.end).unwrap();
+
+                for (name, val) in &node.fields {
+                    if matches!(val, Self::Null) {
+                        continue;
+                    }
+
+                    write!(out, " :{name} ").unwrap();
+                    val.write_sexpr(out);
+                }
+
+                out.push(')');
+            }
+        }
+    }
+}
+
+pub fn to_json(decls: &[Decl]) -> String {
+    decls.dump().to_json()
+}
+
+pub fn to_sexpr(decls: &[Decl]) -> String {
+    decls.dump().to_sexpr()
+}
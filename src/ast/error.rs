@@ -1,19 +1,38 @@
-use crate::parser::Rule;
-use pest::error::LineColLocation;
+use crate::{lexer::TokenKind, span::Span};
+use std::fmt::{self, Display, Formatter};
 
 #[derive(Debug)]
 pub enum AstBuildErrorKind {
     ExpectedNode {
         expected_node: &'static str,
-        rejected_rule: Option<Rule>,
+        found: Option<TokenKind>,
     },
+    ExpectedToken {
+        expected: TokenKind,
+        found: Option<TokenKind>,
+    },
+    ReservedKeyword {
+        keyword: &'static str,
+    },
+    ExpectedStmt {},
     NumOverflow {},
+    ExpectedDigits {},
+    UnexpectedChar {},
+    InvalidEscape {},
+    InvalidChr {},
+    UnterminatedChr {},
+    UnterminatedStr {},
+    UnterminatedBlockComment {},
+    UnescapedBrace {},
+    MlStrDelim {},
+    StrIndent {},
+    TopLevelVar {},
 }
 
 #[derive(Debug)]
 pub struct AstBuildError {
     pub kind: AstBuildErrorKind,
-    pub pos: Option<LineColLocation>,
+    pub span: Span,
 }
 
 pub type AstBuildResult<T> = Result<T, AstBuildError>;
@@ -29,20 +48,68 @@ macro_rules! unreachable_grammar {
 
 pub(crate) use unreachable_grammar;
 
-macro_rules! assert_exhausted {
-    ($pairs:expr, $self:ty) => {
-        debug_assert!(
-            $pairs.len() == 0,
-            "unused pairs left in node: {}",
-            std::any::type_name::<$self>(),
-        )
-    };
+impl From<lexical::Error> for AstBuildErrorKind {
+    fn from(_value: lexical::Error) -> Self {
+        Self::NumOverflow {}
+    }
 }
 
-pub(crate) use assert_exhausted;
+struct Found(Option<TokenKind>);
 
-impl From<lexical::Error> for AstBuildErrorKind {
-    fn from(value: lexical::Error) -> Self {
-        todo!()
+impl Display for Found {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(kind) => write!(f, "{kind}"),
+            None => f.write_str("end of file"),
+        }
     }
 }
+
+impl Display for AstBuildErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ExpectedNode {
+                expected_node,
+                found,
+            } => {
+                let node = expected_node.rsplit("::").next().unwrap_or(expected_node);
+                write!(f, "expected {node}, found {}", Found(*found))
+            }
+            Self::ExpectedToken { expected, found } => {
+                write!(f, "expected {expected}, found {}", Found(*found))
+            }
+            Self::ReservedKeyword { keyword } => write!(f, "`{keyword}` is a reserved keyword"),
+            Self::ExpectedStmt {} => f.write_str("expected a statement, found an expression"),
+            Self::NumOverflow {} => f.write_str("number literal is out of range"),
+            Self::ExpectedDigits {} => f.write_str("expected digits after number prefix"),
+            Self::UnexpectedChar {} => f.write_str("unexpected character"),
+            Self::InvalidEscape {} => f.write_str("unknown escape sequence"),
+            Self::InvalidChr {} => {
+                f.write_str("character literals must contain exactly one character")
+            }
+            Self::UnterminatedChr {} => f.write_str("unterminated character literal"),
+            Self::UnterminatedStr {} => f.write_str("unterminated string literal"),
+            Self::UnterminatedBlockComment {} => f.write_str("unterminated block comment"),
+            Self::UnescapedBrace {} => {
+                f.write_str("unmatched `}` in interpolated string, escape it with `\\}`")
+            }
+            Self::MlStrDelim {} => f.write_str(
+                "the delimiters of a multi-line string must be on their own lines",
+            ),
+            Self::StrIndent {} => f.write_str(
+                "lines in a multi-line string must be indented at least as far as the closing delimiter",
+            ),
+            Self::TopLevelVar {} => f.write_str(
+                "variables can't be declared in the top level scope, use `const` instead",
+            ),
+        }
+    }
+}
+
+impl Display for AstBuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl std::error::Error for AstBuildError {}
@@ -1,49 +1,80 @@
 use crate::{
     ast::{
-        assert_exhausted, match_next, unreachable_grammar, AstBuildResult, AstNode, BoolLit,
-        ChrLit, FloatLit, IfStmt, IntLit, Stmt, StrLit, WhileStmt,
+        match_next, AstBuildErrorKind, AstBuildResult, AstNode, BoolLit, Bounds, ChrLit,
+        DocComment, FloatLit, IfStmt, IntLit, InterpolatedStr, Stmt, StrLit, WhileStmt,
     },
-    parser::{Rule, PRATT_PARSER},
+    lexer::TokenKind,
+    parser::{infix_prec, Assoc, Marker, Parser},
+    span::Span,
+    syntax::NodeKind,
 };
-use pest::iterators::{Pair, Pairs};
 use std::rc::Rc;
+use unicode_normalization::UnicodeNormalization;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Block {
     pub stmts: Vec<Stmt>,
     pub expr: Option<Rc<Expr>>,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Alias {
     pub alias: String,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldVis {
+    Pub,
+    PubGet,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructField {
+    pub doc: Option<DocComment>,
+    pub vis: Option<FieldVis>,
+    pub alias: Alias,
+    pub bounds: Option<Bounds>,
+    pub val: Option<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructExpr {
+    pub default_vis: Option<FieldVis>,
+    pub fields: Vec<StructField>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
 pub enum PostOp {
     Ref,
     Deref,
     Unwrap,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum BinOp {
     EvalPath,
     ExecPath,
+    FnArrow,
     PipeR,
     PipeL,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Expr {
     Alias(Alias),
     Block(Block),
+    Struct(StructExpr),
 
     Bool(BoolLit),
     Int(IntLit),
     Float(FloatLit),
     Chr(ChrLit),
     Str(StrLit),
+    InterpolatedStr(InterpolatedStr),
 
     IfStmt(Rc<IfStmt>),
     WhileStmt(Rc<WhileStmt>),
@@ -51,117 +82,304 @@ pub enum Expr {
     UnaryExpr {
         expr: Rc<Self>,
         op: PostOp,
+        span: Span,
     },
     BinaryExpr {
         lhs: Rc<Self>,
         rhs: Rc<Self>,
         op: BinOp,
+        span: Span,
     },
 }
 
 impl AstNode for Block {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::block).into_inner();
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        match_next!(parser, TokenKind::LBrace);
 
-        let stmts = pairs
-            .by_ref()
-            .map_while(|pair| Stmt::parse(&mut Pairs::single(pair)).transpose())
-            .collect::<Result<Vec<_>, _>>()?;
+        let mut stmts = Vec::new();
+        let mut expr = None;
 
-        let expr = Expr::parse(&mut pairs)?.map(Into::into);
+        while parser.peek() != Some(TokenKind::RBrace) {
+            match Stmt::parse_or_expr(parser)? {
+                Some(Ok(stmt)) => {
+                    parser.expect(TokenKind::Semi)?;
+                    stmts.push(stmt);
+                }
+                Some(Err(tail)) => {
+                    expr = Some(tail.into());
+                    break;
+                }
+                None => break,
+            }
+        }
 
-        assert_exhausted!(pairs, Self);
-        Ok(Some(Self { stmts, expr }))
+        parser.expect(TokenKind::RBrace)?;
+        let span = parser.finish_node(cp, NodeKind::Block);
+        Ok(Some(Self { stmts, expr, span }))
     }
 }
 
 impl AstNode for Alias {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let pair = match_next!(pairs, Rule::alias);
-        let alias = pair.as_str().to_string();
-        Ok(Some(Self { alias }))
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        if let Some(keyword) = parser.peek().and_then(TokenKind::keyword) {
+            return Err(parser.error(AstBuildErrorKind::ReservedKeyword { keyword }));
+        }
+
+        let token = match_next!(parser, TokenKind::Alias);
+        let alias = parser.text(&token).nfc().collect();
+        let span = parser.finish_node(cp, NodeKind::Alias);
+        Ok(Some(Self { alias, span }))
+    }
+}
+
+impl AstNode for FieldVis {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        match_next!(parser, TokenKind::KwPub);
+
+        let is_get = parser.peek() == Some(TokenKind::Colon)
+            && parser.peek_nth(1) == Some(TokenKind::Alias)
+            && parser.peek_text_nth(1) == Some("get");
+
+        let vis = match is_get {
+            true => {
+                parser.bump();
+                parser.bump();
+                Self::PubGet
+            }
+            false => Self::Pub,
+        };
+
+        parser.finish_node(cp, NodeKind::FieldVis);
+        Ok(Some(vis))
+    }
+}
+
+impl AstNode for StructField {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let doc = DocComment::parse(parser)?;
+        let vis = FieldVis::parse(parser)?;
+
+        let alias = match (&doc, &vis) {
+            (None, None) => match Alias::parse(parser)? {
+                Some(alias) => alias,
+                None => return Ok(None),
+            },
+            _ => Alias::expect(parser)?,
+        };
+
+        let bounds = Bounds::parse(parser)?;
+        let val = match parser.next_if(TokenKind::Eq) {
+            Some(_) => Some(Expr::expect(parser)?),
+            None => None,
+        };
+
+        let span = parser.finish_node(cp, NodeKind::StructField);
+        Ok(Some(Self {
+            doc,
+            vis,
+            alias,
+            bounds,
+            val,
+            span,
+        }))
+    }
+}
+
+impl AstNode for StructExpr {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        match_next!(parser, TokenKind::LParen);
+
+        // 'pub *' or 'pub:get *' can only be the first item
+        let default_vis = match parser.peek() {
+            Some(TokenKind::KwPub) => {
+                let star_offset = match parser.peek_nth(1) {
+                    Some(TokenKind::Colon) => 3,
+                    _ => 1,
+                };
+
+                match parser.peek_nth(star_offset) == Some(TokenKind::Star) {
+                    true => {
+                        let vis = FieldVis::expect(parser)?;
+                        parser.expect(TokenKind::Star)?;
+
+                        if parser.peek() != Some(TokenKind::RParen) {
+                            parser.expect(TokenKind::Comma)?;
+                        }
+
+                        Some(vis)
+                    }
+                    false => None,
+                }
+            }
+            _ => None,
+        };
+
+        let mut fields = Vec::new();
+        while let Some(field) = StructField::parse(parser)? {
+            fields.push(field);
+
+            if parser.next_if(TokenKind::Comma).is_none() {
+                break;
+            }
+        }
+
+        parser.expect(TokenKind::RParen)?;
+        let span = parser.finish_node(cp, NodeKind::StructExpr);
+        Ok(Some(Self {
+            default_vis,
+            fields,
+            span,
+        }))
     }
 }
 
 impl AstNode for PostOp {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let op = match pairs.peek().as_ref().map(Pair::as_rule) {
-            Some(Rule::reference) => Self::Ref,
-            Some(Rule::dereference) => Self::Deref,
-            Some(Rule::unwrap) => Self::Unwrap,
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let op = match parser.peek() {
+            Some(TokenKind::Amp) => Self::Ref,
+            Some(TokenKind::Star) => Self::Deref,
+            Some(TokenKind::Question) => Self::Unwrap,
             _ => return Ok(None),
         };
 
-        pairs.next();
-        assert_exhausted!(pairs, Self);
-
+        parser.bump();
         Ok(Some(op))
     }
 }
 
 impl AstNode for BinOp {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let op = match pairs.peek().as_ref().map(Pair::as_rule) {
-            Some(Rule::eval_path) => BinOp::EvalPath,
-            Some(Rule::exec_path) => BinOp::ExecPath,
-            Some(Rule::pipe_r) => BinOp::PipeR,
-            Some(Rule::pipe_l) => BinOp::PipeL,
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let op = match parser.peek() {
+            Some(TokenKind::ColonColon) => BinOp::EvalPath,
+            Some(TokenKind::Dot) => BinOp::ExecPath,
+            Some(TokenKind::Arrow) => BinOp::FnArrow,
+            Some(TokenKind::Gt) => BinOp::PipeR,
+            Some(TokenKind::Lt) => BinOp::PipeL,
             _ => return Ok(None),
         };
 
-        pairs.next();
-        assert_exhausted!(pairs, Self);
-
+        parser.bump();
         Ok(Some(op))
     }
 }
 
 impl AstNode for Expr {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let pairs = match_next!(pairs, Rule::expr).into_inner();
-
-        PRATT_PARSER
-            .map_primary(|pair| {
-                let rule = pair.as_rule();
-                let mut pairs = Pairs::single(pair);
-
-                match rule {
-                    Rule::alias => Alias::expect(&mut pairs).map(Self::Alias),
-                    Rule::block => Block::expect(&mut pairs).map(Self::Block),
-
-                    Rule::bool => BoolLit::expect(&mut pairs).map(Self::Bool),
-                    Rule::int => IntLit::expect(&mut pairs).map(Self::Int),
-                    Rule::float => FloatLit::expect(&mut pairs).map(Self::Float),
-                    Rule::chr => ChrLit::expect(&mut pairs).map(Self::Chr),
-                    Rule::str => StrLit::expect(&mut pairs).map(Self::Str),
-
-                    Rule::if_stmt => IfStmt::expect(&mut pairs).map(Into::into).map(Self::IfStmt),
-                    Rule::while_stmt => WhileStmt::expect(&mut pairs)
-                        .map(Into::into)
-                        .map(Self::WhileStmt),
-
-                    Rule::expr => Self::expect(&mut pairs),
-                    _ => unreachable_grammar!(Self),
-                }
-            })
-            .map_postfix(|expr, op_pair| {
-                let expr = expr?.into();
-
-                let mut op_pairs = Pairs::single(op_pair);
-                let op = PostOp::expect(&mut op_pairs)?;
-
-                Ok(Self::UnaryExpr { expr, op })
-            })
-            .map_infix(|lhs, op_pair, rhs| {
-                let lhs = lhs?.into();
-                let rhs = rhs?.into();
-
-                let mut op_pairs = Pairs::single(op_pair);
-                let op = BinOp::expect(&mut op_pairs)?;
-
-                Ok(Self::BinaryExpr { lhs, rhs, op })
-            })
-            .parse(pairs)
-            .map(Some)
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let Some(lhs) = Self::parse_unary(parser)? else {
+            return Ok(None);
+        };
+
+        Self::parse_infix(parser, cp, lhs, 0).map(Some)
+    }
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Alias(alias) => alias.span,
+            Self::Block(block) => block.span,
+            Self::Struct(expr) => expr.span,
+
+            Self::Bool(lit) => lit.span,
+            Self::Int(lit) => lit.span,
+            Self::Float(lit) => lit.span,
+            Self::Chr(lit) => lit.span,
+            Self::Str(lit) => lit.span,
+            Self::InterpolatedStr(lit) => lit.span,
+
+            Self::IfStmt(stmt) => stmt.span,
+            Self::WhileStmt(stmt) => stmt.span,
+
+            Self::UnaryExpr { span, .. } | Self::BinaryExpr { span, .. } => *span,
+        }
+    }
+
+    fn parse_primary(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let expr = match parser.peek() {
+            Some(TokenKind::Alias) => Alias::expect(parser).map(Self::Alias),
+            Some(TokenKind::LBrace) => Block::expect(parser).map(Self::Block),
+            Some(TokenKind::LParen) => StructExpr::expect(parser).map(Self::Struct),
+
+            Some(TokenKind::KwTrue | TokenKind::KwFalse) => BoolLit::expect(parser).map(Self::Bool),
+            Some(TokenKind::Int) => IntLit::expect(parser).map(Self::Int),
+            Some(TokenKind::Float) => FloatLit::expect(parser).map(Self::Float),
+            Some(TokenKind::Chr) => ChrLit::expect(parser).map(Self::Chr),
+            Some(TokenKind::Str | TokenKind::RawStr | TokenKind::MlStr) => {
+                StrLit::expect(parser).map(Self::Str)
+            }
+            Some(TokenKind::InterpStrStart) => {
+                InterpolatedStr::expect(parser).map(Self::InterpolatedStr)
+            }
+
+            Some(TokenKind::KwIf) => IfStmt::expect(parser).map(Into::into).map(Self::IfStmt),
+            Some(TokenKind::KwWhile) => WhileStmt::expect(parser)
+                .map(Into::into)
+                .map(Self::WhileStmt),
+
+            Some(kind) if kind.keyword().is_some() => Alias::expect(parser).map(Self::Alias),
+            _ => return Ok(None),
+        };
+
+        expr.map(Some)
+    }
+
+    /// postfix operators bind tighter than every infix operator
+    fn parse_unary(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let Some(mut expr) = Self::parse_primary(parser)? else {
+            return Ok(None);
+        };
+
+        while let Some(op) = PostOp::parse(parser)? {
+            expr = Self::UnaryExpr {
+                expr: expr.into(),
+                op,
+                span: parser.finish_node(cp, NodeKind::UnaryExpr),
+            };
+        }
+
+        Ok(Some(expr))
+    }
+
+    /// `cp` is the checkpoint taken before `lhs`, every binary expression built here starts there
+    fn parse_infix(
+        parser: &mut Parser,
+        cp: Marker,
+        mut lhs: Self,
+        min_prec: u8,
+    ) -> AstBuildResult<Self> {
+        while let Some((prec, assoc)) = parser.peek().and_then(infix_prec) {
+            if prec <= min_prec {
+                break;
+            }
+
+            let op = BinOp::expect(parser)?;
+            let rhs_cp = parser.checkpoint();
+            let rhs = match Self::parse_unary(parser)? {
+                Some(rhs) => rhs,
+                None => Self::expect(parser)?,
+            };
+
+            let rhs_prec = match assoc {
+                Assoc::Left => prec,
+                Assoc::Right => prec - 1,
+            };
+
+            let rhs = Self::parse_infix(parser, rhs_cp, rhs, rhs_prec)?;
+            lhs = Self::BinaryExpr {
+                lhs: lhs.into(),
+                rhs: rhs.into(),
+                op,
+                span: parser.finish_node(cp, NodeKind::BinaryExpr),
+            };
+        }
+
+        Ok(lhs)
     }
 }
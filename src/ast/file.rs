@@ -0,0 +1,60 @@
+use crate::{
+    ast::{AliasEval, AstBuildError, AstBuildErrorKind, AstBuildResult, AstNode, Decl},
+    lexer::TokenKind,
+    parser::Parser,
+    span::Span,
+    syntax::NodeKind,
+};
+use std::{any::type_name, path::PathBuf};
+
+/// the root of a source file, it holds every token of the input
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// the file the source was read from, `None` for sources that aren't backed by a file
+    pub path: Option<PathBuf>,
+    pub decls: Vec<Decl>,
+    pub span: Span,
+}
+
+impl SourceFile {
+    /// only constants, types and functions may be declared in the top level scope
+    pub fn check_decl(decl: &Decl) -> AstBuildResult<()> {
+        match decl {
+            Decl::Alias(decl) if matches!(decl.eval, AliasEval::Let | AliasEval::Var) => {
+                Err(AstBuildError {
+                    kind: AstBuildErrorKind::TopLevelVar {},
+                    span: decl.span,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl AstNode for SourceFile {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let root = parser.root();
+        let mut decls = Vec::new();
+
+        // top level decls can be followed by an optional ';'
+        while let Some(decl) = Decl::parse(parser)? {
+            Self::check_decl(&decl)?;
+            parser.next_if(TokenKind::Semi);
+            decls.push(decl);
+        }
+
+        if parser.peek().is_some() {
+            return Err(parser.error(AstBuildErrorKind::ExpectedNode {
+                expected_node: type_name::<Decl>(),
+                found: parser.peek(),
+            }));
+        }
+
+        let span = parser.finish_root(root, NodeKind::Src);
+        Ok(Some(Self {
+            path: None,
+            decls,
+            span,
+        }))
+    }
+}
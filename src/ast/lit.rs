@@ -1,54 +1,71 @@
 use crate::{
-    ast::{assert_exhausted, match_next, unreachable_grammar, AstBuildResult, AstNode},
-    parser::{PairsExt, Rule},
+    ast::{
+        match_next, unreachable_grammar, AstBuildError, AstBuildErrorKind, AstBuildResult, AstNode,
+        Expr,
+    },
+    lexer::{unescape, TokenKind},
+    parser::Parser,
+    span::Span,
+    syntax::NodeKind,
 };
 use lexical::{NumberFormatBuilder, ParseFloatOptions};
-use pest::iterators::{Pair, Pairs};
 use std::num::NonZeroU8;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BoolLit {
     pub val: bool,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FloatLit {
     pub val: f64,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IntLit {
     pub neg: bool,
     pub val: u64,
+    pub span: Span,
 }
 
-#[derive(Debug)]
-pub struct EscSeq {
-    pub val: Box<str>,
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ChrLit {
     pub val: char,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StrLit {
     pub val: String,
+    pub span: Span,
 }
 
-impl AstNode for BoolLit {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::bool).into_inner();
+#[derive(Debug, Clone)]
+pub enum InterpolatedStrPart {
+    Str(String),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone)]
+pub struct InterpolatedStr {
+    pub parts: Vec<InterpolatedStrPart>,
+    pub span: Span,
+}
 
-        let val = match pairs.next().as_ref().map(Pair::as_rule) {
-            Some(Rule::kw_true) => true,
-            Some(Rule::kw_false) => false,
-            _ => unreachable_grammar!(Self),
+impl AstNode for BoolLit {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let val = match parser.peek() {
+            Some(TokenKind::KwTrue) => true,
+            Some(TokenKind::KwFalse) => false,
+            _ => return Ok(None),
         };
 
-        assert_exhausted!(pairs, Self);
-        Ok(Some(Self { val }))
+        parser.bump();
+        let span = parser.finish_node(cp, NodeKind::BoolLit);
+        Ok(Some(Self { val, span }))
     }
 }
 
@@ -62,140 +79,196 @@ const OCT_FMT: u128 = NUM_FMT.radix(8).base_prefix(NonZeroU8::new(b'o')).build()
 const BIN_FMT: u128 = NUM_FMT.radix(2).base_prefix(NonZeroU8::new(b'b')).build();
 
 impl AstNode for FloatLit {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let pair = match_next!(pairs, Rule::float);
-        let bytes = pair.as_str();
-
-        let mut pairs = pair.into_inner();
-        let _sign = pairs.next_if(Rule::num_sign);
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let token = match_next!(parser, TokenKind::Float);
+        let text = parser.text(&token);
+        let unsigned = text.trim_start_matches(['+', '-']);
 
+        // exponents are disabled, but the default 'e' marker would still clash with hex digits
         let opts = ParseFloatOptions::builder()
             .decimal_point(b'.')
+            .exponent(b'^')
             .lossy(true)
             .build()
             .unwrap();
 
-        let parser = match pairs.peek().as_ref().map(Pair::as_rule) {
-            Some(Rule::hex_prefix) => lexical::parse_with_options::<_, _, HEX_FMT>,
-            Some(Rule::oct_prefix) => lexical::parse_with_options::<_, _, OCT_FMT>,
-            Some(Rule::bin_prefix) => lexical::parse_with_options::<_, _, BIN_FMT>,
+        let parse = match unsigned.get(..2) {
+            Some("0x") => lexical::parse_with_options::<_, _, HEX_FMT>,
+            Some("0o") => lexical::parse_with_options::<_, _, OCT_FMT>,
+            Some("0b") => lexical::parse_with_options::<_, _, BIN_FMT>,
             _ => lexical::parse_with_options::<_, _, DEC_FMT>,
         };
 
-        let val = parser(bytes, &opts).unwrap();
+        let val = parse(text, &opts).map_err(|err| AstBuildError {
+            kind: err.into(),
+            span: token.span,
+        })?;
 
-        Ok(Some(Self { val }))
+        let span = parser.finish_node(cp, NodeKind::FloatLit);
+        Ok(Some(Self { val, span }))
     }
 }
 
 impl AstNode for IntLit {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::int).into_inner();
-
-        let sign = pairs
-            .next_if(Rule::num_sign)
-            .map(Pair::into_inner)
-            .as_mut()
-            .and_then(Iterator::next)
-            .as_ref()
-            .map(Pair::as_rule);
-
-        let neg = match sign {
-            Some(Rule::num_neg) => true,
-            Some(Rule::num_pos) | None => false,
-            _ => unreachable_grammar!(Self),
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let token = match_next!(parser, TokenKind::Int);
+        let text = parser.text(&token);
+
+        let neg = text.starts_with('-');
+        let unsigned = text.trim_start_matches(['+', '-']);
+
+        let (radix, digits) = match unsigned.get(..2) {
+            Some("0x") => (16, &unsigned[2..]),
+            Some("0d") => (10, &unsigned[2..]),
+            Some("0o") => (8, &unsigned[2..]),
+            Some("0b") => (2, &unsigned[2..]),
+            _ => (10, unsigned),
         };
 
-        let radix_prefix = match pairs.peek().as_ref().map(Pair::as_rule) {
-            Some(Rule::hex_prefix) => 16,
-            Some(Rule::dec_prefix) => 10,
-            Some(Rule::oct_prefix) => 8,
-            Some(Rule::bin_prefix) => 2,
-            _ => 0,
-        };
+        let val =
+            u64::from_str_radix(&digits.replace('_', ""), radix).map_err(|_| AstBuildError {
+                kind: AstBuildErrorKind::NumOverflow {},
+                span: token.span,
+            })?;
 
-        let radix = if radix_prefix != 0 {
-            pairs.next();
-            radix_prefix
-        } else {
-            10
-        };
-
-        let val_str = pairs
-            .next()
-            .unwrap_or_else(|| unreachable_grammar!(Self))
-            .as_str();
-
-        let val = u64::from_str_radix(val_str, radix).unwrap();
-
-        assert_exhausted!(pairs, Self);
-        Ok(Some(Self { neg, val }))
+        let span = parser.finish_node(cp, NodeKind::IntLit);
+        Ok(Some(Self { neg, val, span }))
     }
 }
 
-impl AstNode for EscSeq {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::esc_seq).into_inner();
-        let pair = pairs.next().unwrap_or_else(|| unreachable_grammar!(Self));
-        let val = match pair.as_rule() {
-            Rule::esc_lit => match pair.as_str() {
-                "t" => "\t".into(),
-                "r" => "\r".into(),
-                "n" => "\n".into(),
-                "'" => "'".into(),
-                "\"" => "\"".into(),
-                "\\" => "\\".into(),
-                _ => unreachable_grammar!(Self),
-            },
-            _ => unreachable_grammar!(Self),
-        };
+/// decodes the escape sequences in `text`, unknown escapes have already been rejected by the lexer
+fn unescape_into(text: &str, val: &mut String) {
+    let mut chars = text.chars();
 
-        assert_exhausted!(pairs, Self);
-        Ok(Some(Self { val }))
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => val.extend(chars.next().and_then(unescape)),
+            ch => val.push(ch),
+        }
     }
 }
 
 impl AstNode for ChrLit {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::chr).into_inner();
-
-        let esc = EscSeq::parse(&mut pairs)?;
-        let content = pairs.next_if(Rule::chr_content);
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let token = match_next!(parser, TokenKind::Chr);
+        let text = parser.text(&token);
 
-        let lit = esc
-            .as_ref()
-            .map(|esc| esc.val.as_ref())
-            .or(content.as_ref().map(Pair::as_str))
-            .unwrap_or_else(|| unreachable_grammar!(Self));
+        let mut val = String::new();
+        unescape_into(&text[1..text.len() - 1], &mut val);
 
-        let mut chars = lit.chars();
+        let mut chars = val.chars();
         let Some(val) = chars.next() else {
             unreachable_grammar!(Self);
         };
 
         debug_assert!(chars.next().is_none());
-        assert_exhausted!(pairs, Self);
-
-        Ok(Some(Self { val }))
+        let span = parser.finish_node(cp, NodeKind::ChrLit);
+        Ok(Some(Self { val, span }))
     }
 }
 
 impl AstNode for StrLit {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let pairs = match_next!(pairs, Rule::str).into_inner();
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let Some(kind @ (TokenKind::Str | TokenKind::RawStr | TokenKind::MlStr)) = parser.peek()
+        else {
+            return Ok(None);
+        };
+
+        let token = parser.bump().unwrap();
+        let text = parser.text(&token);
         let mut val = String::new();
 
-        for pair in pairs {
-            match pair.as_rule() {
-                Rule::str_content => val.push_str(pair.as_str()),
-                Rule::esc_seq => {
-                    let esc = EscSeq::expect(&mut Pairs::single(pair))?;
-                    val.push_str(esc.val.as_ref());
+        match kind {
+            TokenKind::Str => unescape_into(&text[1..text.len() - 1], &mut val),
+            TokenKind::RawStr => {
+                let hashes = text[1..].len() - text[1..].trim_start_matches('#').len();
+                val.push_str(&text[hashes + 2..text.len() - hashes - 1]);
+            }
+            _ => ml_str_strip(text, token.span.start, &mut val)?,
+        }
+
+        let span = parser.finish_node(cp, NodeKind::StrLit);
+        Ok(Some(Self { val, span }))
+    }
+}
+
+/// strips the indentation of the closing delimiter from every line of a multi-line string.
+/// whitespace only lines are emptied, every other line must begin with the indentation
+fn ml_str_strip(text: &str, offset: usize, val: &mut String) -> AstBuildResult<()> {
+    let body_start = text.find('\n').map_or(text.len(), |i| i + 1);
+    let body = &text[body_start..text.len() - 3];
+
+    // the last line only holds the indentation and the closing delimiter
+    let Some(last_line) = body.rfind('\n') else {
+        return Ok(());
+    };
+
+    let indent = &body[last_line + 1..];
+    let content = &body[..last_line];
+    let content = content.strip_suffix('\r').unwrap_or(content);
+
+    let mut line_start = offset + body_start;
+    for (i, line) in content.split('\n').enumerate() {
+        let trimmed = line.strip_suffix('\r').unwrap_or(line);
+
+        if i > 0 {
+            val.push('\n');
+        }
+
+        if trimmed.chars().all(|ch| ch == ' ' || ch == '\t') {
+            // whitespace only lines are emptied
+        } else if let Some(rest) = trimmed.strip_prefix(indent) {
+            unescape_into(rest, val);
+        } else {
+            let indent_len = trimmed.len() - trimmed.trim_start_matches([' ', '\t']).len();
+            return Err(AstBuildError {
+                kind: AstBuildErrorKind::StrIndent {},
+                span: Span::empty(line_start + indent_len),
+            });
+        }
+
+        line_start += line.len() + 1;
+    }
+
+    Ok(())
+}
+
+impl AstNode for InterpolatedStr {
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        match_next!(parser, TokenKind::InterpStrStart);
+        let mut parts = Vec::new();
+
+        loop {
+            let token = parser.bump();
+            let text = match token.map(|token| token.kind) {
+                Some(TokenKind::InterpStrEnd) => break,
+                Some(TokenKind::InterpStrText) => parser.text(&token.unwrap()),
+                Some(TokenKind::InterpExprStart) => {
+                    let expr = Expr::expect(parser)?;
+                    parser.expect(TokenKind::InterpExprEnd)?;
+
+                    parts.push(InterpolatedStrPart::Expr(expr));
+                    continue;
                 }
                 _ => unreachable_grammar!(Self),
+            };
+
+            match parts.last_mut() {
+                Some(InterpolatedStrPart::Str(val)) => unescape_into(text, val),
+                _ => {
+                    let mut val = String::new();
+                    unescape_into(text, &mut val);
+                    parts.push(InterpolatedStrPart::Str(val));
+                }
             }
         }
 
-        Ok(Some(Self { val }))
+        let span = parser.finish_node(cp, NodeKind::InterpolatedStr);
+        Ok(Some(Self { parts, span }))
     }
 }
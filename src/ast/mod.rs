@@ -1,13 +1,19 @@
 mod decl;
+mod dump;
 mod error;
 mod expr;
+mod file;
 mod lit;
 mod node;
 mod stmt;
+mod visit;
 
 pub use decl::*;
+pub use dump::*;
 pub use error::*;
 pub use expr::*;
+pub use file::*;
 pub use lit::*;
 pub use node::*;
 pub use stmt::*;
+pub use visit::*;
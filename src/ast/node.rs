@@ -1,27 +1,18 @@
 use crate::{
-    ast::{AstBuildError, AstBuildErrorKind, AstBuildResult},
-    parser::Rule,
-};
-use pest::{
-    error::LineColLocation,
-    iterators::{Pair, Pairs},
+    ast::{AstBuildErrorKind, AstBuildResult},
+    parser::Parser,
 };
 use std::{any::type_name, fmt::Debug};
 
 pub trait AstNode: Sized + Debug {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>>;
-
-    fn expect(pairs: &mut Pairs<Rule>) -> AstBuildResult<Self> {
-        let Some(node) = Self::parse(pairs)? else {
-            let next = pairs.peek();
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>>;
 
-            return Err(AstBuildError {
-                pos: next.as_ref().map(Pair::line_col).map(LineColLocation::Pos),
-                kind: AstBuildErrorKind::ExpectedNode {
-                    expected_node: type_name::<Self>(),
-                    rejected_rule: next.as_ref().map(Pair::as_rule),
-                },
-            });
+    fn expect(parser: &mut Parser) -> AstBuildResult<Self> {
+        let Some(node) = Self::parse(parser)? else {
+            return Err(parser.error(AstBuildErrorKind::ExpectedNode {
+                expected_node: type_name::<Self>(),
+                found: parser.peek(),
+            }));
         };
 
         Ok(node)
@@ -29,9 +20,9 @@ pub trait AstNode: Sized + Debug {
 }
 
 macro_rules! match_next {
-    ($pairs:expr, $rule:expr) => {
-        match crate::parser::PairsExt::next_if($pairs, $rule) {
-            Some(pair) => pair,
+    ($parser:expr, $kind:expr) => {
+        match $parser.next_if($kind) {
+            Some(token) => token,
             None => return Ok(None),
         }
     };
@@ -1,16 +1,20 @@
 use crate::{
-    ast::{assert_exhausted, match_next, unreachable_grammar, AstBuildResult, AstNode, Decl, Expr},
-    parser::Rule,
+    ast::{match_next, AstBuildError, AstBuildErrorKind, AstBuildResult, AstNode, Decl, Expr},
+    lexer::TokenKind,
+    parser::Parser,
+    span::Span,
+    syntax::NodeKind,
 };
-use pest::iterators::{Pair, Pairs};
+use std::rc::Rc;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AssignStmt {
     pub lhs: Expr,
     pub rhs: Expr,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum CtrlOp {
     Return,
     Defer,
@@ -18,26 +22,29 @@ pub enum CtrlOp {
     Break,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CtrlStmt {
     pub op: CtrlOp,
     pub rhs: Option<Expr>,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IfStmt {
     pub lhs: Expr,
     pub rhs: Expr,
     pub chain: Option<Expr>,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WhileStmt {
     pub lhs: Expr,
     pub rhs: Expr,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Stmt {
     Assign(AssignStmt),
     Ctrl(CtrlStmt),
@@ -47,88 +54,131 @@ pub enum Stmt {
     Decl(Decl),
 }
 
-impl AstNode for AssignStmt {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::assign_stmt).into_inner();
-
-        let lhs = Expr::expect(&mut pairs)?;
-        let rhs = Expr::expect(&mut pairs)?;
-
-        assert_exhausted!(pairs, Self);
-        Ok(Some(Self { lhs, rhs }))
-    }
-}
-
 impl AstNode for CtrlOp {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::ctrl_op).into_inner();
-
-        let op = match pairs.next().as_ref().map(Pair::as_rule) {
-            Some(Rule::kw_return) => Self::Return,
-            Some(Rule::kw_defer) => Self::Defer,
-            Some(Rule::kw_continue) => Self::Continue,
-            Some(Rule::kw_break) => Self::Break,
-            _ => unreachable_grammar!(Self),
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let op = match parser.peek() {
+            Some(TokenKind::KwReturn) => Self::Return,
+            Some(TokenKind::KwDefer) => Self::Defer,
+            Some(TokenKind::KwContinue) => Self::Continue,
+            Some(TokenKind::KwBreak) => Self::Break,
+            _ => return Ok(None),
         };
 
-        assert_exhausted!(pairs, Self);
+        parser.bump();
         Ok(Some(op))
     }
 }
 
 impl AstNode for CtrlStmt {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::ctrl_stmt).into_inner();
-
-        let op = CtrlOp::expect(&mut pairs)?;
-        let rhs = Expr::parse(&mut pairs)?;
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        let Some(op) = CtrlOp::parse(parser)? else {
+            return Ok(None);
+        };
 
-        assert_exhausted!(pairs, Self);
-        Ok(Some(Self { op, rhs }))
+        let rhs = Expr::parse(parser)?;
+        let span = parser.finish_node(cp, NodeKind::CtrlStmt);
+        Ok(Some(Self { op, rhs, span }))
     }
 }
 
 impl AstNode for IfStmt {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::if_stmt).into_inner();
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        match_next!(parser, TokenKind::KwIf);
 
-        let lhs = Expr::expect(&mut pairs)?;
-        let rhs = Expr::expect(&mut pairs)?;
-        let chain = Expr::parse(&mut pairs)?;
+        let lhs = Expr::expect(parser)?;
+        let rhs = Expr::expect(parser)?;
+
+        let chain = match parser.next_if(TokenKind::KwElse) {
+            Some(_) => Some(Expr::expect(parser)?),
+            None => None,
+        };
 
-        assert_exhausted!(pairs, Self);
-        Ok(Some(Self { lhs, rhs, chain }))
+        let span = parser.finish_node(cp, NodeKind::IfStmt);
+        Ok(Some(Self {
+            lhs,
+            rhs,
+            chain,
+            span,
+        }))
     }
 }
 
 impl AstNode for WhileStmt {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::while_stmt).into_inner();
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let cp = parser.checkpoint();
+        match_next!(parser, TokenKind::KwWhile);
 
-        let lhs = Expr::expect(&mut pairs)?;
-        let rhs = Expr::expect(&mut pairs)?;
+        let lhs = Expr::expect(parser)?;
+        let rhs = Expr::expect(parser)?;
 
-        assert_exhausted!(pairs, Self);
-        Ok(Some(Self { lhs, rhs }))
+        let span = parser.finish_node(cp, NodeKind::WhileStmt);
+        Ok(Some(Self { lhs, rhs, span }))
     }
 }
 
 impl AstNode for Stmt {
-    fn parse(pairs: &mut Pairs<Rule>) -> AstBuildResult<Option<Self>> {
-        let mut pairs = match_next!(pairs, Rule::stmt).into_inner();
+    fn parse(parser: &mut Parser) -> AstBuildResult<Option<Self>> {
+        let start = parser.peek_span();
+        let stmt = match Self::parse_or_expr(parser)? {
+            Some(Ok(stmt)) => stmt,
+            Some(Err(Expr::IfStmt(stmt))) => Self::If(Rc::into_inner(stmt).unwrap()),
+            Some(Err(Expr::WhileStmt(stmt))) => Self::While(Rc::into_inner(stmt).unwrap()),
+            Some(Err(_)) => {
+                return Err(AstBuildError {
+                    kind: AstBuildErrorKind::ExpectedStmt {},
+                    span: parser.span_from(start),
+                });
+            }
+            None => return Ok(None),
+        };
 
-        let stmt = match pairs.peek().as_ref().map(Pair::as_rule) {
-            Some(Rule::assign_stmt) => AssignStmt::expect(&mut pairs).map(Self::Assign),
-            Some(Rule::ctrl_stmt) => CtrlStmt::expect(&mut pairs).map(Self::Ctrl),
-            Some(Rule::if_stmt) => IfStmt::expect(&mut pairs).map(Self::If),
-            Some(Rule::while_stmt) => WhileStmt::expect(&mut pairs).map(Self::While),
+        Ok(Some(stmt))
+    }
+}
 
-            Some(Rule::decl) => Decl::expect(&mut pairs).map(Self::Decl),
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Assign(stmt) => stmt.span,
+            Self::Ctrl(stmt) => stmt.span,
+            Self::If(stmt) => stmt.span,
+            Self::While(stmt) => stmt.span,
+
+            Self::Decl(decl) => decl.span(),
+        }
+    }
+
+    /// parses a statement, or the expression that ends a block. `if` and `while` are only
+    /// statements when followed by a ';', otherwise they are the value of the block
+    pub fn parse_or_expr(parser: &mut Parser) -> AstBuildResult<Option<Result<Self, Expr>>> {
+        if let Some(decl) = Decl::parse(parser)? {
+            return Ok(Some(Ok(Self::Decl(decl))));
+        }
+
+        if let Some(ctrl) = CtrlStmt::parse(parser)? {
+            return Ok(Some(Ok(Self::Ctrl(ctrl))));
+        }
+
+        let cp = parser.checkpoint();
+        let Some(lhs) = Expr::parse(parser)? else {
+            return Ok(None);
+        };
 
-            _ => unreachable_grammar!(Self),
+        if parser.next_if(TokenKind::Eq).is_some() {
+            let rhs = Expr::expect(parser)?;
+            let span = parser.finish_node(cp, NodeKind::AssignStmt);
+            return Ok(Some(Ok(Self::Assign(AssignStmt { lhs, rhs, span }))));
+        }
+
+        let is_stmt = parser.peek() == Some(TokenKind::Semi);
+        let stmt = match lhs {
+            Expr::IfStmt(stmt) if is_stmt => Self::If(Rc::into_inner(stmt).unwrap()),
+            Expr::WhileStmt(stmt) if is_stmt => Self::While(Rc::into_inner(stmt).unwrap()),
+            expr => return Ok(Some(Err(expr))),
         };
 
-        assert_exhausted!(pairs, Self);
-        stmt.map(Into::into)
+        Ok(Some(Ok(stmt)))
     }
 }
@@ -0,0 +1,669 @@
+use crate::ast::{
+    Alias, AliasDecl, AliasEval, AssignStmt, BinOp, Block, BoolLit, Bounds, ChrLit, CtrlOp,
+    CtrlStmt, Decl, DocComment, Expr, FieldVis, FloatLit, FnDecl, IfStmt, IfaceAliasItem,
+    IfaceDecl, IfaceFnItem, IfaceItem, IntLit, InterpolatedStr, InterpolatedStrPart, PostOp,
+    SourceFile, Stmt, StrLit, StructExpr, StructField, WhileStmt,
+};
+use std::rc::Rc;
+
+/// a read only pass over the AST. every `visit_*` method defaults to the matching `walk_*`
+/// function, which visits the children of the node in source order. overriding a method and not
+/// calling `walk_*` skips the children. nodes are borrowed for `'ast`, so a visitor can keep
+/// references to them
+pub trait Visitor<'ast> {
+    fn visit_source_file(&mut self, file: &'ast SourceFile) {
+        walk_source_file(self, file);
+    }
+
+    fn visit_decl(&mut self, decl: &'ast Decl) {
+        walk_decl(self, decl);
+    }
+
+    fn visit_alias_decl(&mut self, decl: &'ast AliasDecl) {
+        walk_alias_decl(self, decl);
+    }
+
+    fn visit_fn_decl(&mut self, decl: &'ast FnDecl) {
+        walk_fn_decl(self, decl);
+    }
+
+    fn visit_iface_decl(&mut self, decl: &'ast IfaceDecl) {
+        walk_iface_decl(self, decl);
+    }
+
+    fn visit_iface_item(&mut self, item: &'ast IfaceItem) {
+        walk_iface_item(self, item);
+    }
+
+    fn visit_iface_alias_item(&mut self, item: &'ast IfaceAliasItem) {
+        walk_iface_alias_item(self, item);
+    }
+
+    fn visit_iface_fn_item(&mut self, item: &'ast IfaceFnItem) {
+        walk_iface_fn_item(self, item);
+    }
+
+    fn visit_doc_comment(&mut self, _doc: &'ast DocComment) {}
+
+    fn visit_bounds(&mut self, bounds: &'ast Bounds) {
+        walk_bounds(self, bounds);
+    }
+
+    fn visit_alias_eval(&mut self, _eval: &'ast AliasEval) {}
+
+    fn visit_alias(&mut self, _alias: &'ast Alias) {}
+
+    fn visit_block(&mut self, block: &'ast Block) {
+        walk_block(self, block);
+    }
+
+    fn visit_struct_expr(&mut self, expr: &'ast StructExpr) {
+        walk_struct_expr(self, expr);
+    }
+
+    fn visit_struct_field(&mut self, field: &'ast StructField) {
+        walk_struct_field(self, field);
+    }
+
+    fn visit_field_vis(&mut self, _vis: &'ast FieldVis) {}
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_post_op(&mut self, _op: &'ast PostOp) {}
+
+    fn visit_bin_op(&mut self, _op: &'ast BinOp) {}
+
+    fn visit_bool_lit(&mut self, _lit: &'ast BoolLit) {}
+
+    fn visit_int_lit(&mut self, _lit: &'ast IntLit) {}
+
+    fn visit_float_lit(&mut self, _lit: &'ast FloatLit) {}
+
+    fn visit_chr_lit(&mut self, _lit: &'ast ChrLit) {}
+
+    fn visit_str_lit(&mut self, _lit: &'ast StrLit) {}
+
+    fn visit_interpolated_str(&mut self, lit: &'ast InterpolatedStr) {
+        walk_interpolated_str(self, lit);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_assign_stmt(&mut self, stmt: &'ast AssignStmt) {
+        walk_assign_stmt(self, stmt);
+    }
+
+    fn visit_ctrl_stmt(&mut self, stmt: &'ast CtrlStmt) {
+        walk_ctrl_stmt(self, stmt);
+    }
+
+    fn visit_ctrl_op(&mut self, _op: &'ast CtrlOp) {}
+
+    fn visit_if_stmt(&mut self, stmt: &'ast IfStmt) {
+        walk_if_stmt(self, stmt);
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &'ast WhileStmt) {
+        walk_while_stmt(self, stmt);
+    }
+}
+
+pub fn walk_source_file<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, file: &'ast SourceFile) {
+    for decl in &file.decls {
+        visitor.visit_decl(decl);
+    }
+}
+
+pub fn walk_decl<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, decl: &'ast Decl) {
+    match decl {
+        Decl::Alias(decl) => visitor.visit_alias_decl(decl),
+        Decl::Fn(decl) => visitor.visit_fn_decl(decl),
+        Decl::Iface(decl) => visitor.visit_iface_decl(decl),
+    }
+}
+
+pub fn walk_alias_decl<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, decl: &'ast AliasDecl) {
+    if let Some(doc) = &decl.doc {
+        visitor.visit_doc_comment(doc);
+    }
+
+    visitor.visit_alias_eval(&decl.eval);
+    visitor.visit_alias(&decl.alias);
+
+    if let Some(bounds) = &decl.bounds {
+        visitor.visit_bounds(bounds);
+    }
+
+    visitor.visit_expr(&decl.rhs);
+}
+
+pub fn walk_fn_decl<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, decl: &'ast FnDecl) {
+    if let Some(doc) = &decl.doc {
+        visitor.visit_doc_comment(doc);
+    }
+
+    if let Some(owner) = &decl.owner {
+        visitor.visit_alias(owner);
+    }
+
+    visitor.visit_alias(&decl.alias);
+
+    if let Some(bounds) = &decl.bounds {
+        visitor.visit_bounds(bounds);
+    }
+
+    visitor.visit_block(&decl.body);
+}
+
+pub fn walk_iface_decl<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, decl: &'ast IfaceDecl) {
+    if let Some(doc) = &decl.doc {
+        visitor.visit_doc_comment(doc);
+    }
+
+    visitor.visit_alias(&decl.alias);
+
+    for item in &decl.items {
+        visitor.visit_iface_item(item);
+    }
+}
+
+pub fn walk_iface_item<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, item: &'ast IfaceItem) {
+    match item {
+        IfaceItem::Alias(item) => visitor.visit_iface_alias_item(item),
+        IfaceItem::Fn(item) => visitor.visit_iface_fn_item(item),
+    }
+}
+
+pub fn walk_iface_alias_item<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    item: &'ast IfaceAliasItem,
+) {
+    if let Some(doc) = &item.doc {
+        visitor.visit_doc_comment(doc);
+    }
+
+    visitor.visit_alias_eval(&item.eval);
+    visitor.visit_alias(&item.alias);
+
+    if let Some(bounds) = &item.bounds {
+        visitor.visit_bounds(bounds);
+    }
+
+    if let Some(default) = &item.default {
+        visitor.visit_expr(default);
+    }
+}
+
+pub fn walk_iface_fn_item<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    item: &'ast IfaceFnItem,
+) {
+    if let Some(doc) = &item.doc {
+        visitor.visit_doc_comment(doc);
+    }
+
+    visitor.visit_alias(&item.alias);
+
+    if let Some(bounds) = &item.bounds {
+        visitor.visit_bounds(bounds);
+    }
+
+    if let Some(default) = &item.default {
+        visitor.visit_block(default);
+    }
+}
+
+pub fn walk_bounds<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, bounds: &'ast Bounds) {
+    visitor.visit_expr(&bounds.expr);
+}
+
+pub fn walk_block<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, block: &'ast Block) {
+    for stmt in &block.stmts {
+        visitor.visit_stmt(stmt);
+    }
+
+    if let Some(expr) = &block.expr {
+        visitor.visit_expr(expr);
+    }
+}
+
+pub fn walk_struct_expr<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: &'ast StructExpr) {
+    if let Some(vis) = &expr.default_vis {
+        visitor.visit_field_vis(vis);
+    }
+
+    for field in &expr.fields {
+        visitor.visit_struct_field(field);
+    }
+}
+
+pub fn walk_struct_field<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    field: &'ast StructField,
+) {
+    if let Some(doc) = &field.doc {
+        visitor.visit_doc_comment(doc);
+    }
+
+    if let Some(vis) = &field.vis {
+        visitor.visit_field_vis(vis);
+    }
+
+    visitor.visit_alias(&field.alias);
+
+    if let Some(bounds) = &field.bounds {
+        visitor.visit_bounds(bounds);
+    }
+
+    if let Some(val) = &field.val {
+        visitor.visit_expr(val);
+    }
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: &'ast Expr) {
+    match expr {
+        Expr::Alias(alias) => visitor.visit_alias(alias),
+        Expr::Block(block) => visitor.visit_block(block),
+        Expr::Struct(expr) => visitor.visit_struct_expr(expr),
+
+        Expr::Bool(lit) => visitor.visit_bool_lit(lit),
+        Expr::Int(lit) => visitor.visit_int_lit(lit),
+        Expr::Float(lit) => visitor.visit_float_lit(lit),
+        Expr::Chr(lit) => visitor.visit_chr_lit(lit),
+        Expr::Str(lit) => visitor.visit_str_lit(lit),
+        Expr::InterpolatedStr(lit) => visitor.visit_interpolated_str(lit),
+
+        Expr::IfStmt(stmt) => visitor.visit_if_stmt(stmt),
+        Expr::WhileStmt(stmt) => visitor.visit_while_stmt(stmt),
+
+        Expr::UnaryExpr { expr, op, .. } => {
+            visitor.visit_expr(expr);
+            visitor.visit_post_op(op);
+        }
+        Expr::BinaryExpr { lhs, rhs, op, .. } => {
+            visitor.visit_expr(lhs);
+            visitor.visit_bin_op(op);
+            visitor.visit_expr(rhs);
+        }
+    }
+}
+
+pub fn walk_interpolated_str<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    lit: &'ast InterpolatedStr,
+) {
+    for part in &lit.parts {
+        if let InterpolatedStrPart::Expr(expr) = part {
+            visitor.visit_expr(expr);
+        }
+    }
+}
+
+pub fn walk_stmt<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast Stmt) {
+    match stmt {
+        Stmt::Assign(stmt) => visitor.visit_assign_stmt(stmt),
+        Stmt::Ctrl(stmt) => visitor.visit_ctrl_stmt(stmt),
+        Stmt::If(stmt) => visitor.visit_if_stmt(stmt),
+        Stmt::While(stmt) => visitor.visit_while_stmt(stmt),
+
+        Stmt::Decl(decl) => visitor.visit_decl(decl),
+    }
+}
+
+pub fn walk_assign_stmt<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast AssignStmt) {
+    visitor.visit_expr(&stmt.lhs);
+    visitor.visit_expr(&stmt.rhs);
+}
+
+pub fn walk_ctrl_stmt<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast CtrlStmt) {
+    visitor.visit_ctrl_op(&stmt.op);
+
+    if let Some(rhs) = &stmt.rhs {
+        visitor.visit_expr(rhs);
+    }
+}
+
+pub fn walk_if_stmt<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast IfStmt) {
+    visitor.visit_expr(&stmt.lhs);
+    visitor.visit_expr(&stmt.rhs);
+
+    if let Some(chain) = &stmt.chain {
+        visitor.visit_expr(chain);
+    }
+}
+
+pub fn walk_while_stmt<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast WhileStmt) {
+    visitor.visit_expr(&stmt.lhs);
+    visitor.visit_expr(&stmt.rhs);
+}
+
+/// a pass that rewrites the AST in place. shared `Rc` children are cloned before they are
+/// visited so a rewrite never leaks into another tree
+pub trait VisitorMut {
+    fn visit_source_file_mut(&mut self, file: &mut SourceFile) {
+        walk_source_file_mut(self, file);
+    }
+
+    fn visit_decl_mut(&mut self, decl: &mut Decl) {
+        walk_decl_mut(self, decl);
+    }
+
+    fn visit_alias_decl_mut(&mut self, decl: &mut AliasDecl) {
+        walk_alias_decl_mut(self, decl);
+    }
+
+    fn visit_fn_decl_mut(&mut self, decl: &mut FnDecl) {
+        walk_fn_decl_mut(self, decl);
+    }
+
+    fn visit_iface_decl_mut(&mut self, decl: &mut IfaceDecl) {
+        walk_iface_decl_mut(self, decl);
+    }
+
+    fn visit_iface_item_mut(&mut self, item: &mut IfaceItem) {
+        walk_iface_item_mut(self, item);
+    }
+
+    fn visit_iface_alias_item_mut(&mut self, item: &mut IfaceAliasItem) {
+        walk_iface_alias_item_mut(self, item);
+    }
+
+    fn visit_iface_fn_item_mut(&mut self, item: &mut IfaceFnItem) {
+        walk_iface_fn_item_mut(self, item);
+    }
+
+    fn visit_doc_comment_mut(&mut self, _doc: &mut DocComment) {}
+
+    fn visit_bounds_mut(&mut self, bounds: &mut Bounds) {
+        walk_bounds_mut(self, bounds);
+    }
+
+    fn visit_alias_eval_mut(&mut self, _eval: &mut AliasEval) {}
+
+    fn visit_alias_mut(&mut self, _alias: &mut Alias) {}
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        walk_block_mut(self, block);
+    }
+
+    fn visit_struct_expr_mut(&mut self, expr: &mut StructExpr) {
+        walk_struct_expr_mut(self, expr);
+    }
+
+    fn visit_struct_field_mut(&mut self, field: &mut StructField) {
+        walk_struct_field_mut(self, field);
+    }
+
+    fn visit_field_vis_mut(&mut self, _vis: &mut FieldVis) {}
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_post_op_mut(&mut self, _op: &mut PostOp) {}
+
+    fn visit_bin_op_mut(&mut self, _op: &mut BinOp) {}
+
+    fn visit_bool_lit_mut(&mut self, _lit: &mut BoolLit) {}
+
+    fn visit_int_lit_mut(&mut self, _lit: &mut IntLit) {}
+
+    fn visit_float_lit_mut(&mut self, _lit: &mut FloatLit) {}
+
+    fn visit_chr_lit_mut(&mut self, _lit: &mut ChrLit) {}
+
+    fn visit_str_lit_mut(&mut self, _lit: &mut StrLit) {}
+
+    fn visit_interpolated_str_mut(&mut self, lit: &mut InterpolatedStr) {
+        walk_interpolated_str_mut(self, lit);
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt);
+    }
+
+    fn visit_assign_stmt_mut(&mut self, stmt: &mut AssignStmt) {
+        walk_assign_stmt_mut(self, stmt);
+    }
+
+    fn visit_ctrl_stmt_mut(&mut self, stmt: &mut CtrlStmt) {
+        walk_ctrl_stmt_mut(self, stmt);
+    }
+
+    fn visit_ctrl_op_mut(&mut self, _op: &mut CtrlOp) {}
+
+    fn visit_if_stmt_mut(&mut self, stmt: &mut IfStmt) {
+        walk_if_stmt_mut(self, stmt);
+    }
+
+    fn visit_while_stmt_mut(&mut self, stmt: &mut WhileStmt) {
+        walk_while_stmt_mut(self, stmt);
+    }
+}
+
+pub fn walk_source_file_mut<V: VisitorMut + ?Sized>(visitor: &mut V, file: &mut SourceFile) {
+    for decl in &mut file.decls {
+        visitor.visit_decl_mut(decl);
+    }
+}
+
+pub fn walk_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut Decl) {
+    match decl {
+        Decl::Alias(decl) => visitor.visit_alias_decl_mut(decl),
+        Decl::Fn(decl) => visitor.visit_fn_decl_mut(decl),
+        Decl::Iface(decl) => visitor.visit_iface_decl_mut(decl),
+    }
+}
+
+pub fn walk_alias_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut AliasDecl) {
+    if let Some(doc) = &mut decl.doc {
+        visitor.visit_doc_comment_mut(doc);
+    }
+
+    visitor.visit_alias_eval_mut(&mut decl.eval);
+    visitor.visit_alias_mut(&mut decl.alias);
+
+    if let Some(bounds) = &mut decl.bounds {
+        visitor.visit_bounds_mut(bounds);
+    }
+
+    visitor.visit_expr_mut(&mut decl.rhs);
+}
+
+pub fn walk_fn_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut FnDecl) {
+    if let Some(doc) = &mut decl.doc {
+        visitor.visit_doc_comment_mut(doc);
+    }
+
+    if let Some(owner) = &mut decl.owner {
+        visitor.visit_alias_mut(owner);
+    }
+
+    visitor.visit_alias_mut(&mut decl.alias);
+
+    if let Some(bounds) = &mut decl.bounds {
+        visitor.visit_bounds_mut(bounds);
+    }
+
+    visitor.visit_block_mut(&mut decl.body);
+}
+
+pub fn walk_iface_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut IfaceDecl) {
+    if let Some(doc) = &mut decl.doc {
+        visitor.visit_doc_comment_mut(doc);
+    }
+
+    visitor.visit_alias_mut(&mut decl.alias);
+
+    for item in &mut decl.items {
+        visitor.visit_iface_item_mut(item);
+    }
+}
+
+pub fn walk_iface_item_mut<V: VisitorMut + ?Sized>(visitor: &mut V, item: &mut IfaceItem) {
+    match item {
+        IfaceItem::Alias(item) => visitor.visit_iface_alias_item_mut(item),
+        IfaceItem::Fn(item) => visitor.visit_iface_fn_item_mut(item),
+    }
+}
+
+pub fn walk_iface_alias_item_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    item: &mut IfaceAliasItem,
+) {
+    if let Some(doc) = &mut item.doc {
+        visitor.visit_doc_comment_mut(doc);
+    }
+
+    visitor.visit_alias_eval_mut(&mut item.eval);
+    visitor.visit_alias_mut(&mut item.alias);
+
+    if let Some(bounds) = &mut item.bounds {
+        visitor.visit_bounds_mut(bounds);
+    }
+
+    if let Some(default) = &mut item.default {
+        visitor.visit_expr_mut(default);
+    }
+}
+
+pub fn walk_iface_fn_item_mut<V: VisitorMut + ?Sized>(visitor: &mut V, item: &mut IfaceFnItem) {
+    if let Some(doc) = &mut item.doc {
+        visitor.visit_doc_comment_mut(doc);
+    }
+
+    visitor.visit_alias_mut(&mut item.alias);
+
+    if let Some(bounds) = &mut item.bounds {
+        visitor.visit_bounds_mut(bounds);
+    }
+
+    if let Some(default) = &mut item.default {
+        visitor.visit_block_mut(default);
+    }
+}
+
+pub fn walk_bounds_mut<V: VisitorMut + ?Sized>(visitor: &mut V, bounds: &mut Bounds) {
+    visitor.visit_expr_mut(&mut bounds.expr);
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut Block) {
+    for stmt in &mut block.stmts {
+        visitor.visit_stmt_mut(stmt);
+    }
+
+    if let Some(expr) = &mut block.expr {
+        visitor.visit_expr_mut(Rc::make_mut(expr));
+    }
+}
+
+pub fn walk_struct_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut StructExpr) {
+    if let Some(vis) = &mut expr.default_vis {
+        visitor.visit_field_vis_mut(vis);
+    }
+
+    for field in &mut expr.fields {
+        visitor.visit_struct_field_mut(field);
+    }
+}
+
+pub fn walk_struct_field_mut<V: VisitorMut + ?Sized>(visitor: &mut V, field: &mut StructField) {
+    if let Some(doc) = &mut field.doc {
+        visitor.visit_doc_comment_mut(doc);
+    }
+
+    if let Some(vis) = &mut field.vis {
+        visitor.visit_field_vis_mut(vis);
+    }
+
+    visitor.visit_alias_mut(&mut field.alias);
+
+    if let Some(bounds) = &mut field.bounds {
+        visitor.visit_bounds_mut(bounds);
+    }
+
+    if let Some(val) = &mut field.val {
+        visitor.visit_expr_mut(val);
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Alias(alias) => visitor.visit_alias_mut(alias),
+        Expr::Block(block) => visitor.visit_block_mut(block),
+        Expr::Struct(expr) => visitor.visit_struct_expr_mut(expr),
+
+        Expr::Bool(lit) => visitor.visit_bool_lit_mut(lit),
+        Expr::Int(lit) => visitor.visit_int_lit_mut(lit),
+        Expr::Float(lit) => visitor.visit_float_lit_mut(lit),
+        Expr::Chr(lit) => visitor.visit_chr_lit_mut(lit),
+        Expr::Str(lit) => visitor.visit_str_lit_mut(lit),
+        Expr::InterpolatedStr(lit) => visitor.visit_interpolated_str_mut(lit),
+
+        Expr::IfStmt(stmt) => visitor.visit_if_stmt_mut(Rc::make_mut(stmt)),
+        Expr::WhileStmt(stmt) => visitor.visit_while_stmt_mut(Rc::make_mut(stmt)),
+
+        Expr::UnaryExpr { expr, op, .. } => {
+            visitor.visit_expr_mut(Rc::make_mut(expr));
+            visitor.visit_post_op_mut(op);
+        }
+        Expr::BinaryExpr { lhs, rhs, op, .. } => {
+            visitor.visit_expr_mut(Rc::make_mut(lhs));
+            visitor.visit_bin_op_mut(op);
+            visitor.visit_expr_mut(Rc::make_mut(rhs));
+        }
+    }
+}
+
+pub fn walk_interpolated_str_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    lit: &mut InterpolatedStr,
+) {
+    for part in &mut lit.parts {
+        if let InterpolatedStrPart::Expr(expr) = part {
+            visitor.visit_expr_mut(expr);
+        }
+    }
+}
+
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::Assign(stmt) => visitor.visit_assign_stmt_mut(stmt),
+        Stmt::Ctrl(stmt) => visitor.visit_ctrl_stmt_mut(stmt),
+        Stmt::If(stmt) => visitor.visit_if_stmt_mut(stmt),
+        Stmt::While(stmt) => visitor.visit_while_stmt_mut(stmt),
+
+        Stmt::Decl(decl) => visitor.visit_decl_mut(decl),
+    }
+}
+
+pub fn walk_assign_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut AssignStmt) {
+    visitor.visit_expr_mut(&mut stmt.lhs);
+    visitor.visit_expr_mut(&mut stmt.rhs);
+}
+
+pub fn walk_ctrl_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut CtrlStmt) {
+    visitor.visit_ctrl_op_mut(&mut stmt.op);
+
+    if let Some(rhs) = &mut stmt.rhs {
+        visitor.visit_expr_mut(rhs);
+    }
+}
+
+pub fn walk_if_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut IfStmt) {
+    visitor.visit_expr_mut(&mut stmt.lhs);
+    visitor.visit_expr_mut(&mut stmt.rhs);
+
+    if let Some(chain) = &mut stmt.chain {
+        visitor.visit_expr_mut(chain);
+    }
+}
+
+pub fn walk_while_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut WhileStmt) {
+    visitor.visit_expr_mut(&mut stmt.lhs);
+    visitor.visit_expr_mut(&mut stmt.rhs);
+}
@@ -0,0 +1,222 @@
+use crate::{
+    ast::{write_str, AstBuildError},
+    span::Span,
+};
+use std::fmt::{self, Display, Formatter, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// a secondary location that explains a diagnostic, e.g. the declaration of an assigned alias
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    pub labels: Vec<Label>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(message, span)
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+
+        self
+    }
+}
+
+impl From<AstBuildError> for Diagnostic {
+    fn from(value: AstBuildError) -> Self {
+        Self::error(value.kind.to_string(), value.span)
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => f.write_str("error"),
+            Self::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// writes escape codes only when colour is enabled
+struct Style(bool);
+
+impl Style {
+    fn paint(&self, code: &str, text: impl Display) -> String {
+        match self.0 {
+            true => format!("{code}{text}{RESET}"),
+            false => text.to_string(),
+        }
+    }
+}
+
+impl Diagnostic {
+    /// renders the diagnostic for a terminal, with the source line under every span
+    pub fn render(&self, path: &str, src: &str, color: bool) -> String {
+        let style = Style(color);
+        let severity_color = match self.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        let mut out = format!(
+            "{}{}\n",
+            style.paint(severity_color, self.severity),
+            style.paint(BOLD, format_args!(": {}", self.message)),
+        );
+
+        let gutter = [self.span]
+            .iter()
+            .chain(self.labels.iter().map(|label| &label.span))
+            .map(|span| span.line_col(src).0.to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        let snippet = Snippet {
+            style,
+            path,
+            src,
+            gutter,
+        };
+
+        snippet.write(&mut out, self.span, '^', "", severity_color);
+        for label in &self.labels {
+            snippet.write(&mut out, label.span, '-', &label.message, BLUE);
+        }
+
+        out
+    }
+
+    /// the diagnostic as a single line JSON object. lines and columns are one based, spans are
+    /// byte offsets
+    pub fn to_json(&self, path: &str, src: &str) -> String {
+        let mut out = String::from("{\"severity\":");
+        write_str(&mut out, &self.severity.to_string());
+        out.push_str(",\"message\":");
+        write_str(&mut out, &self.message);
+        out.push_str(",\"path\":");
+        write_str(&mut out, path);
+        write_location(&mut out, src, self.span);
+
+        out.push_str(",\"labels\":[");
+        for (i, label) in self.labels.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+
+            out.push_str("{\"message\":");
+            write_str(&mut out, &label.message);
+            write_location(&mut out, src, label.span);
+            out.push('}');
+        }
+
+        out.push_str("]}");
+        out
+    }
+}
+
+fn write_location(out: &mut String, src: &str, span: Span) {
+    let (line, col) = span.line_col(src);
+    write!(
+        out,
+        ",\"span\":[{},{}],\"line\":{line},\"column\":{col}",
+        span.start, span.end
+    )
+    .unwrap();
+}
+
+/// the parts of a rendered diagnostic that are shared by all of its spans
+struct Snippet<'a> {
+    style: Style,
+    path: &'a str,
+    src: &'a str,
+    gutter: usize,
+}
+
+impl Snippet<'_> {
+    /// writes the location of `span` and the source line it starts on, underlined with
+    /// `marker`. spans over several lines are underlined to the end of their first line
+    fn write(&self, out: &mut String, span: Span, marker: char, message: &str, color: &str) {
+        let Self {
+            style,
+            path,
+            src,
+            gutter,
+        } = self;
+
+        let gutter = *gutter;
+        let (line, col) = span.line_col(src);
+        let start = span.start.min(src.len());
+        let line_start = src[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = src[start..].find('\n').map_or(src.len(), |i| start + i);
+        let text = src[line_start..line_end].trim_end_matches('\r');
+
+        let pad = " ".repeat(gutter);
+        let bar = style.paint(BLUE, "|");
+        writeln!(out, "{pad}{} {path}:{line}:{col}", style.paint(BLUE, "-->")).unwrap();
+        writeln!(out, "{pad} {bar}").unwrap();
+        writeln!(
+            out,
+            "{} {bar} {text}",
+            style.paint(BLUE, format_args!("{line:>gutter$}"))
+        )
+        .unwrap();
+
+        // tabs are kept so the underline lines up with the source however wide they are shown
+        let indent = src[line_start..start]
+            .chars()
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+
+        let end = span.end.clamp(start, line_end.max(start));
+        let width = src[start..end].chars().count().max(1);
+        let underline = marker.to_string().repeat(width);
+        let underline = match message.is_empty() {
+            true => underline,
+            false => format!("{underline} {message}"),
+        };
+
+        writeln!(out, "{pad} {bar} {indent}{}", style.paint(color, underline)).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{
+    ast::SourceFile,
+    diagnostic::Diagnostic,
+    eval::{Evaluator, Value},
+    sema::{DefId, Resolution},
+};
+use std::collections::HashMap;
+
+/// how many loop iterations and calls evaluating one constant can take
+pub const CONST_STEP_LIMIT: usize = 1_000_000;
+
+/// the values of the constants in a file
+#[derive(Debug, Default)]
+pub struct Consts {
+    pub values: HashMap<DefId, Value>,
+}
+
+/// evaluates every `const` in a file, including those declared in fns. constants can use
+/// other constants and call fns, but can't depend on themselves
+pub fn eval_consts(file: &SourceFile, res: &Resolution) -> (Consts, Vec<Diagnostic>) {
+    let mut evaluator = Evaluator::new(file, res).with_step_limit(CONST_STEP_LIMIT);
+    let decls: Vec<_> = evaluator
+        .const_decls()
+        .iter()
+        .filter_map(|decl| Some((evaluator.def_id(&decl.alias)?, decl.alias.span)))
+        .collect();
+
+    for (id, span) in decls {
+        // errors are collected by the evaluator
+        let _ = evaluator.const_value(id, span);
+    }
+
+    let (values, errors) = evaluator.finish();
+    (Consts { values }, errors)
+}
@@ -0,0 +1,773 @@
+use crate::{
+    ast::{
+        walk_alias_decl, walk_fn_decl, Alias, AliasDecl, AliasEval, BinOp, Block, CtrlOp, Decl,
+        Expr, FnDecl, IfStmt, InterpolatedStrPart, PostOp, SourceFile, Stmt, StructExpr,
+        StructField, Visitor, WhileStmt,
+    },
+    diagnostic::Diagnostic,
+    eval::Value,
+    sema::{DefId, DefKind, Resolution},
+    span::Span,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+/// how deep fn calls can be nested before evaluation stops. a call takes about 10KB of stack in
+/// debug builds, so runaway recursion is reported before it overflows the main thread's stack,
+/// or the 2MB stack that the test harness gives each test
+const MAX_DEPTH: usize = 128;
+
+/// the ways evaluation leaves an expression early
+#[derive(Debug)]
+pub enum Flow {
+    Return(Value),
+    Break(Span),
+    Continue(Span),
+    Error(Diagnostic),
+    /// evaluation failed and the error has already been reported
+    Failed,
+}
+
+pub type EvalResult<T> = Result<T, Flow>;
+
+fn error<T>(message: impl Into<String>, span: Span) -> EvalResult<T> {
+    Err(Flow::Error(Diagnostic::error(message, span)))
+}
+
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Alias(&'a AliasDecl),
+    Fn(&'a FnDecl),
+}
+
+/// the locals of a fn call, a slot is shared by every reference to it
+pub type Frame = HashMap<DefId, Rc<RefCell<Value>>>;
+
+/// evaluates the expressions of a resolved file. constants are evaluated once, on first use,
+/// and can't depend on themselves
+pub struct Evaluator<'a> {
+    res: &'a Resolution,
+    items: HashMap<DefId, Item<'a>>,
+    /// every `const` in the file, in source order
+    const_decls: Vec<&'a AliasDecl>,
+    consts: HashMap<DefId, Value>,
+    failed: HashSet<DefId>,
+    /// the constants being evaluated, and where each was used
+    pending: Vec<(DefId, Span)>,
+    frames: Vec<Frame>,
+    /// the expressions deferred in every block being evaluated
+    defers: Vec<Vec<&'a Expr>>,
+    steps: usize,
+    step_limit: Option<usize>,
+    errors: Vec<Diagnostic>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(file: &'a SourceFile, res: &'a Resolution) -> Self {
+        let mut evaluator = Self {
+            res,
+            items: HashMap::new(),
+            const_decls: Vec::new(),
+            consts: HashMap::new(),
+            failed: HashSet::new(),
+            pending: Vec::new(),
+            frames: Vec::new(),
+            defers: Vec::new(),
+            steps: 0,
+            step_limit: None,
+            errors: Vec::new(),
+        };
+
+        evaluator.visit_source_file(file);
+        evaluator
+    }
+
+    /// stops evaluating a constant after `limit` loop iterations and calls
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// reuses constants that were evaluated before
+    pub fn with_consts(mut self, consts: HashMap<DefId, Value>) -> Self {
+        self.consts = consts;
+        self
+    }
+
+    pub fn const_decls(&self) -> &[&'a AliasDecl] {
+        &self.const_decls
+    }
+
+    pub fn def_id(&self, alias: &Alias) -> Option<DefId> {
+        self.res.decls.get(&alias.span).copied()
+    }
+
+    /// the evaluated constants and the errors reported while evaluating them
+    pub fn finish(self) -> (HashMap<DefId, Value>, Vec<Diagnostic>) {
+        (self.consts, self.errors)
+    }
+
+    /// evaluates the following statements with `frame` as their locals
+    pub fn push_frame(&mut self, frame: Frame) {
+        self.frames.push(frame);
+    }
+
+    pub fn pop_frame(&mut self) -> Option<Frame> {
+        self.frames.pop()
+    }
+
+    fn step(&mut self, span: Span) -> EvalResult<()> {
+        self.steps += 1;
+        match self.step_limit {
+            Some(limit) if self.steps > limit => error("evaluation took too long", span),
+            _ => Ok(()),
+        }
+    }
+
+    fn local(&self, id: DefId) -> Option<Rc<RefCell<Value>>> {
+        self.frames.last()?.get(&id).cloned()
+    }
+
+    fn bind(&mut self, id: DefId, val: Value) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insert(id, Rc::new(RefCell::new(val)));
+        }
+    }
+
+    /// the value of a constant. errors are reported once, with the constant that caused them,
+    /// later uses fail silently
+    pub fn const_value(&mut self, id: DefId, span: Span) -> EvalResult<Value> {
+        if let Some(val) = self.consts.get(&id) {
+            return Ok(val.clone());
+        }
+
+        if self.failed.contains(&id) {
+            return Err(Flow::Failed);
+        }
+
+        if let Some(start) = self.pending.iter().position(|(pending, _)| *pending == id) {
+            return Err(Flow::Error(self.cycle(start, span)));
+        }
+
+        let Some(Item::Alias(decl)) = self.items.get(&id).copied() else {
+            return Err(Flow::Failed);
+        };
+
+        if self.pending.is_empty() {
+            self.steps = 0;
+        }
+
+        // constants can't see the locals of the fn they are used in
+        let frames = std::mem::replace(&mut self.frames, vec![Frame::new()]);
+        self.pending.push((id, span));
+        let result = self.expr(&decl.rhs);
+        self.pending.pop();
+        self.frames = frames;
+
+        let err = match result {
+            Ok(val) => {
+                self.consts.insert(id, val.clone());
+                return Ok(val);
+            }
+            Err(Flow::Error(err)) => err,
+            Err(Flow::Failed) => {
+                self.failed.insert(id);
+                return Err(Flow::Failed);
+            }
+            Err(Flow::Return(_)) => {
+                Diagnostic::error("`return` can't be used outside of a fn", decl.rhs.span())
+            }
+            Err(Flow::Break(span) | Flow::Continue(span)) => {
+                Diagnostic::error("`break` and `continue` can only be used in a loop", span)
+            }
+        };
+
+        self.errors.push(err);
+        self.failed.insert(id);
+        Err(Flow::Failed)
+    }
+
+    /// the error for a constant that is used while it is being evaluated. every constant in the
+    /// cycle fails
+    fn cycle(&mut self, start: usize, span: Span) -> Diagnostic {
+        let cycle: Vec<_> = self.pending[start..].iter().map(|(id, _)| *id).collect();
+        let uses = self.pending[start + 1..]
+            .iter()
+            .map(|(_, span)| *span)
+            .chain([span]);
+
+        let name = |id: DefId| &self.res.def(id).name;
+        let first = cycle[0];
+        let mut err = Diagnostic::error(
+            format!("cycle detected when evaluating `{}`", name(first)),
+            self.res.def(first).span.unwrap_or(span),
+        );
+
+        for (i, span) in uses.enumerate() {
+            let next = cycle.get(i + 1).copied().unwrap_or(first);
+            err = err.with_label(span, format!("`{}` uses `{}`", name(cycle[i]), name(next)));
+        }
+
+        self.failed.extend(cycle);
+        err
+    }
+
+    pub fn expr(&mut self, expr: &'a Expr) -> EvalResult<Value> {
+        match expr {
+            Expr::Alias(alias) => self.alias(alias),
+            Expr::Block(block) => self.block(block),
+            Expr::Struct(expr) => self.struct_value(expr),
+
+            Expr::Bool(lit) => Ok(Value::Bool(lit.val)),
+            Expr::Int(lit) => {
+                let val = lit.val as i128;
+                Ok(Value::Int(if lit.neg { -val } else { val }))
+            }
+            Expr::Float(lit) => Ok(Value::Float(lit.val)),
+            Expr::Chr(lit) => Ok(Value::Chr(lit.val)),
+            Expr::Str(lit) => Ok(Value::Str(lit.val.clone())),
+            Expr::InterpolatedStr(lit) => {
+                let mut out = String::new();
+                for part in &lit.parts {
+                    match part {
+                        InterpolatedStrPart::Str(text) => out.push_str(text),
+                        InterpolatedStrPart::Expr(expr) => {
+                            out.push_str(&self.expr(expr)?.to_text())
+                        }
+                    }
+                }
+
+                Ok(Value::Str(out))
+            }
+
+            Expr::IfStmt(stmt) => self.if_stmt(stmt),
+            Expr::WhileStmt(stmt) => self.while_stmt(stmt),
+
+            Expr::UnaryExpr { expr, op, span } => match op {
+                PostOp::Ref => {
+                    let slot = match &**expr {
+                        Expr::Alias(alias) => self
+                            .res
+                            .uses
+                            .get(&alias.span)
+                            .and_then(|id| self.local(*id)),
+                        _ => None,
+                    };
+
+                    match slot {
+                        Some(slot) => Ok(Value::Ref(slot)),
+                        None => Ok(Value::Ref(Rc::new(RefCell::new(self.expr(expr)?)))),
+                    }
+                }
+                PostOp::Deref => match self.expr(expr)? {
+                    Value::Ref(slot) => Ok(slot.borrow().clone()),
+                    val => error(
+                        format!("`{val}` isn't a reference, it can't be dereferenced"),
+                        *span,
+                    ),
+                },
+                PostOp::Unwrap => error("unwrapping can't be evaluated yet", *span),
+            },
+            Expr::BinaryExpr { lhs, rhs, op, span } => match (op, &**rhs) {
+                (BinOp::ExecPath, Expr::Alias(field)) => {
+                    let val = self.expr(lhs)?;
+                    match val.field(&field.alias) {
+                        Some(val) => Ok(val),
+                        None => error(
+                            format!("`{val}` has no field `{}`", field.alias),
+                            field.span,
+                        ),
+                    }
+                }
+                (BinOp::EvalPath, Expr::Block(block)) => self.cast(lhs, block),
+                (BinOp::EvalPath, Expr::Alias(method)) => self.alias(method),
+                (BinOp::EvalPath, Expr::Struct(fields)) => {
+                    self.construct(lhs, &fields.fields, fields.span)
+                }
+                (BinOp::PipeR, _) => {
+                    let arg = self.expr(lhs)?;
+                    let callee = self.expr(rhs)?;
+                    self.call(callee, arg, *span)
+                }
+                (BinOp::PipeL, _) => {
+                    let callee = self.expr(lhs)?;
+                    let arg = self.expr(rhs)?;
+                    self.call(callee, arg, *span)
+                }
+                _ => error("this expression can't be evaluated yet", *span),
+            },
+        }
+    }
+
+    fn alias(&mut self, alias: &Alias) -> EvalResult<Value> {
+        let Some(id) = self.res.uses.get(&alias.span).copied() else {
+            return Err(Flow::Failed);
+        };
+
+        let def = self.res.def(id);
+        match def.kind {
+            DefKind::Const => self.const_value(id, alias.span),
+            DefKind::Fn => Ok(Value::Fn {
+                def: id,
+                name: def.name.clone(),
+                args: Vec::new(),
+            }),
+            // fns without a signature take `()`, the resolver doesn't give their `arg` a span
+            DefKind::Arg if def.span.is_none() => Ok(Value::unit()),
+            DefKind::Let | DefKind::Var | DefKind::Arg | DefKind::SelfArg | DefKind::Ret => {
+                match self.local(id) {
+                    Some(slot) => Ok(slot.borrow().clone()),
+                    None => error(
+                        format!(
+                            "`{}` isn't a constant, its value isn't known at compile time",
+                            alias.alias
+                        ),
+                        alias.span,
+                    ),
+                }
+            }
+            DefKind::Prim | DefKind::Type | DefKind::Iface | DefKind::SelfType => {
+                error(format!("`{}` isn't a value", alias.alias), alias.span)
+            }
+        }
+    }
+
+    /// evaluates a block, then the expressions deferred in it in reverse order. deferred
+    /// expressions run when the block is left early too, but not after an error
+    pub fn block(&mut self, block: &'a Block) -> EvalResult<Value> {
+        self.defers.push(Vec::new());
+        let result = self.block_body(block);
+        let defers = self.defers.pop().unwrap_or_default();
+
+        if let Err(Flow::Error(_) | Flow::Failed) = result {
+            return result;
+        }
+
+        for expr in defers.into_iter().rev() {
+            self.expr(expr)?;
+        }
+
+        result
+    }
+
+    fn block_body(&mut self, block: &'a Block) -> EvalResult<Value> {
+        for stmt in &block.stmts {
+            self.stmt(stmt)?;
+        }
+
+        match &block.expr {
+            Some(expr) => self.expr(expr),
+            None => Ok(Value::unit()),
+        }
+    }
+
+    pub fn stmt(&mut self, stmt: &'a Stmt) -> EvalResult<()> {
+        match stmt {
+            Stmt::Decl(Decl::Alias(decl)) => {
+                let Some(id) = self.def_id(&decl.alias) else {
+                    return Err(Flow::Failed);
+                };
+
+                match decl.eval {
+                    AliasEval::Let | AliasEval::Var => {
+                        let val = self.expr(&decl.rhs)?;
+                        self.bind(id, val);
+                    }
+                    AliasEval::Const => {
+                        self.const_value(id, decl.alias.span)?;
+                    }
+                    AliasEval::Type => {}
+                }
+            }
+            Stmt::Decl(_) => {}
+            Stmt::Assign(stmt) => {
+                let mut val = Some(self.expr(&stmt.rhs)?);
+                self.modify(&stmt.lhs, &mut |slot| {
+                    *slot = val.take().unwrap_or_else(Value::unit);
+                    Ok(())
+                })?;
+            }
+            Stmt::Ctrl(stmt) => match stmt.op {
+                CtrlOp::Return => {
+                    let val = match &stmt.rhs {
+                        Some(rhs) => self.expr(rhs)?,
+                        None => Value::unit(),
+                    };
+
+                    return Err(Flow::Return(val));
+                }
+                CtrlOp::Break => return Err(Flow::Break(stmt.span)),
+                CtrlOp::Continue => return Err(Flow::Continue(stmt.span)),
+                CtrlOp::Defer => {
+                    if let (Some(rhs), Some(defers)) = (&stmt.rhs, self.defers.last_mut()) {
+                        defers.push(rhs);
+                    }
+                }
+            },
+            Stmt::If(stmt) => {
+                self.if_stmt(stmt)?;
+            }
+            Stmt::While(stmt) => {
+                self.while_stmt(stmt)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// applies `f` to the slot that `place` names, fields are reached through references
+    fn modify(
+        &mut self,
+        place: &'a Expr,
+        f: &mut dyn FnMut(&mut Value) -> EvalResult<()>,
+    ) -> EvalResult<()> {
+        match place {
+            Expr::Alias(alias) => {
+                let slot = self
+                    .res
+                    .uses
+                    .get(&alias.span)
+                    .and_then(|id| self.local(*id));
+
+                match slot {
+                    Some(slot) => f(&mut slot.borrow_mut()),
+                    None => error(
+                        format!("`{}` can't be assigned here", alias.alias),
+                        alias.span,
+                    ),
+                }
+            }
+            Expr::BinaryExpr {
+                lhs,
+                rhs,
+                op: BinOp::ExecPath,
+                ..
+            } if matches!(&**rhs, Expr::Alias(_)) => {
+                let Expr::Alias(field) = &**rhs else {
+                    unreachable!()
+                };
+
+                self.modify(lhs, &mut |val| {
+                    let mut set = |val: &mut Value| match val.field_mut(&field.alias) {
+                        Some(slot) => f(slot),
+                        None => error(
+                            format!("`{val}` has no field `{}`", field.alias),
+                            field.span,
+                        ),
+                    };
+
+                    match val {
+                        Value::Ref(slot) => set(&mut slot.clone().borrow_mut()),
+                        val => set(val),
+                    }
+                })
+            }
+            Expr::UnaryExpr {
+                expr,
+                op: PostOp::Deref,
+                span,
+            } => match self.expr(expr)? {
+                Value::Ref(slot) => f(&mut slot.borrow_mut()),
+                val => error(
+                    format!("`{val}` isn't a reference, it can't be dereferenced"),
+                    *span,
+                ),
+            },
+            place => error("this can't be assigned to", place.span()),
+        }
+    }
+
+    fn if_stmt(&mut self, stmt: &'a IfStmt) -> EvalResult<Value> {
+        if self.cond(&stmt.lhs)? {
+            return self.expr(&stmt.rhs);
+        }
+
+        match &stmt.chain {
+            Some(chain) => self.expr(chain),
+            None => Ok(Value::unit()),
+        }
+    }
+
+    fn while_stmt(&mut self, stmt: &'a WhileStmt) -> EvalResult<Value> {
+        while self.cond(&stmt.lhs)? {
+            self.step(stmt.span)?;
+            match self.expr(&stmt.rhs) {
+                Ok(_) | Err(Flow::Continue(_)) => {}
+                Err(Flow::Break(_)) => break,
+                Err(flow) => return Err(flow),
+            }
+        }
+
+        Ok(Value::unit())
+    }
+
+    fn cond(&mut self, cond: &'a Expr) -> EvalResult<bool> {
+        match self.expr(cond)? {
+            Value::Bool(val) => Ok(val),
+            val => error(format!("expected a `bool`, found `{val}`"), cond.span()),
+        }
+    }
+
+    fn struct_value(&mut self, expr: &'a StructExpr) -> EvalResult<Value> {
+        let mut fields = Vec::new();
+        for field in &expr.fields {
+            let Some(val) = &field.val else {
+                return error(
+                    format!("field `{}` has no value", field.alias.alias),
+                    field.span,
+                );
+            };
+
+            fields.push((field.alias.alias.clone(), self.expr(val)?));
+        }
+
+        Ok(Value::Struct(fields))
+    }
+
+    /// casts don't change the value, only the order of struct fields
+    fn cast(&mut self, ty: &'a Expr, value: &'a Block) -> EvalResult<Value> {
+        match (self.block(value)?, self.struct_ty(ty)) {
+            (Value::Struct(mut fields), Some(ty)) => {
+                fields.sort_by_key(|(name, _)| {
+                    ty.fields
+                        .iter()
+                        .position(|field| &field.alias.alias == name)
+                });
+
+                Ok(Value::Struct(fields))
+            }
+            (val, _) => Ok(val),
+        }
+    }
+
+    /// the fields of the struct type that `ty` names
+    fn struct_ty(&self, ty: &'a Expr) -> Option<&'a StructExpr> {
+        match ty {
+            Expr::Struct(ty) => Some(ty),
+            Expr::Alias(alias) => {
+                let id = self.res.uses.get(&alias.span)?;
+                match self.items.get(id)? {
+                    Item::Alias(decl) if matches!(decl.eval, AliasEval::Type) => {
+                        self.struct_ty(&decl.rhs)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// builds `ty::(fields)`, fields that aren't given take their defaults. struct literals
+    /// given to struct fields are built the same way
+    fn construct(
+        &mut self,
+        ty: &'a Expr,
+        given: &'a [StructField],
+        span: Span,
+    ) -> EvalResult<Value> {
+        let Some(decl) = self.struct_ty(ty) else {
+            return error("this type can't be constructed with fields", ty.span());
+        };
+
+        let mut fields = Vec::new();
+        for field in &decl.fields {
+            let name = &field.alias.alias;
+            let field_ty = field.bounds.as_ref().map(|bounds| &bounds.expr);
+            let nested = field_ty.filter(|ty| self.struct_ty(ty).is_some());
+
+            let val = given
+                .iter()
+                .find(|given| &given.alias.alias == name)
+                .and_then(|given| given.val.as_ref());
+
+            let val = match (val, nested) {
+                (Some(Expr::Struct(val)), Some(ty)) => self.construct(ty, &val.fields, val.span)?,
+                (Some(val), _) => self.expr(val)?,
+                (None, _) if field.val.is_some() => self.expr(field.val.as_ref().unwrap())?,
+                (None, Some(ty)) => self.construct(ty, &[], span)?,
+                (None, None) => return error(format!("missing field `{name}`"), span),
+            };
+
+            fields.push((name.clone(), val));
+        }
+
+        Ok(Value::Struct(fields))
+    }
+
+    /// calls a fn with one more argument. a fn with a receiver takes two arguments, and is
+    /// only run once it has both
+    pub fn call(&mut self, callee: Value, arg: Value, span: Span) -> EvalResult<Value> {
+        let Value::Fn {
+            def,
+            name,
+            mut args,
+        } = callee
+        else {
+            return error(format!("`{callee}` isn't a fn, it can't be called"), span);
+        };
+
+        let Some(Item::Fn(decl)) = self.items.get(&def).copied() else {
+            return Err(Flow::Failed);
+        };
+
+        let (params, ret) = match &decl.bounds {
+            Some(bounds) => {
+                let mut params = Vec::new();
+                let mut expr = &bounds.expr;
+                while let Expr::BinaryExpr {
+                    lhs,
+                    rhs,
+                    op: BinOp::FnArrow,
+                    ..
+                } = expr
+                {
+                    params.push(&**lhs);
+                    expr = rhs;
+                }
+
+                params.truncate(2);
+                (params, Some(expr))
+            }
+            None => (Vec::new(), None),
+        };
+
+        args.push(arg);
+        if args.len() < params.len() {
+            return Ok(Value::Fn { def, name, args });
+        }
+
+        self.step(span)?;
+        if self.frames.len() > MAX_DEPTH {
+            return error("too many nested calls", span);
+        }
+
+        self.frames.push(Frame::new());
+        if params.is_empty() {
+            // a signature without arrows is only a return type, `arg` is declared at it
+            let id = decl
+                .bounds
+                .as_ref()
+                .and_then(|bounds| self.res.decls.get(&bounds.span));
+            if let (Some(id), Some(arg)) = (id.copied(), args.pop()) {
+                self.bind(id, arg);
+            }
+        }
+
+        for (param, arg) in params.iter().zip(args) {
+            self.bind_param(param, arg);
+        }
+
+        // named return fields are returned once the body ends
+        let ret_fields: Vec<_> = match ret {
+            Some(Expr::Struct(ret)) if !ret.fields.is_empty() => ret
+                .fields
+                .iter()
+                .filter_map(|field| Some((field.alias.alias.clone(), self.def_id(&field.alias)?)))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        for (_, id) in &ret_fields {
+            self.bind(*id, Value::unit());
+        }
+
+        let result = match self.block(&decl.body) {
+            Ok(_) if decl.body.expr.is_none() && !ret_fields.is_empty() => {
+                let fields = ret_fields
+                    .iter()
+                    .map(|(name, id)| {
+                        let val = self.local(*id).map(|slot| slot.borrow().clone());
+                        (name.clone(), val.unwrap_or_else(Value::unit))
+                    })
+                    .collect();
+
+                Ok(Value::Struct(fields))
+            }
+            Ok(val) | Err(Flow::Return(val)) => Ok(val),
+            Err(Flow::Break(span) | Flow::Continue(span)) => {
+                error("`break` and `continue` can only be used in a loop", span)
+            }
+            Err(Flow::Error(err))
+                if err.labels.len() < 8 && err.labels.iter().all(|label| label.span != span) =>
+            {
+                Err(Flow::Error(
+                    err.with_label(span, format!("in this call to `{name}`")),
+                ))
+            }
+            Err(flow) => Err(flow),
+        };
+
+        self.frames.pop();
+        result
+    }
+
+    /// binds the names in a parameter the same way the resolver declares them
+    fn bind_param(&mut self, param: &'a Expr, arg: Value) {
+        match param {
+            Expr::BinaryExpr {
+                rhs,
+                op: BinOp::EvalPath,
+                ..
+            } if matches!(&**rhs, Expr::Block(_) | Expr::Struct(_)) => match &**rhs {
+                Expr::Block(Block {
+                    stmts,
+                    expr: Some(tail),
+                    ..
+                }) if stmts.is_empty() => {
+                    if let Some(id) = match &**tail {
+                        Expr::Alias(alias) => self.def_id(alias),
+                        _ => None,
+                    } {
+                        self.bind(id, arg);
+                    }
+                }
+                Expr::Struct(fields) => self.bind_fields(&fields.fields, &arg),
+                _ => {}
+            },
+            param => {
+                if let Expr::Struct(fields) = param {
+                    self.bind_fields(&fields.fields, &arg);
+                }
+
+                if let Some(id) = self.res.decls.get(&param.span()).copied() {
+                    self.bind(id, arg);
+                }
+            }
+        }
+    }
+
+    fn bind_fields(&mut self, fields: &'a [StructField], arg: &Value) {
+        for field in fields {
+            if let Some(id) = self.def_id(&field.alias) {
+                let val = arg.field(&field.alias.alias).unwrap_or_else(Value::unit);
+                self.bind(id, val);
+            }
+        }
+    }
+}
+
+/// records the fns and bindings of a file, and its constants in source order
+impl<'a> Visitor<'a> for Evaluator<'a> {
+    fn visit_alias_decl(&mut self, decl: &'a AliasDecl) {
+        if let Some(id) = self.def_id(&decl.alias) {
+            self.items.insert(id, Item::Alias(decl));
+        }
+
+        if matches!(decl.eval, AliasEval::Const) {
+            self.const_decls.push(decl);
+        }
+
+        walk_alias_decl(self, decl);
+    }
+
+    fn visit_fn_decl(&mut self, decl: &'a FnDecl) {
+        if let Some(id) = self.def_id(&decl.alias) {
+            self.items.insert(id, Item::Fn(decl));
+        }
+
+        walk_fn_decl(self, decl);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{
+    ast::{Decl, SourceFile},
+    diagnostic::Diagnostic,
+    eval::{Evaluator, Flow, Value},
+    sema::Analysis,
+    span::Span,
+};
+
+/// runs a program by calling its top level `main` fn with `()`, and returns the value `main`
+/// returns. the file must have been analyzed without errors
+pub fn run(file: &SourceFile, analysis: &Analysis) -> Result<Value, Diagnostic> {
+    let main = file.decls.iter().find_map(|decl| match decl {
+        Decl::Fn(decl) if decl.owner.is_none() && decl.alias.alias == "main" => Some(decl),
+        _ => None,
+    });
+
+    let Some(main) = main else {
+        return Err(Diagnostic::error(
+            "`main` isn't declared, programs start by calling it",
+            Span::empty(file.span.start),
+        ));
+    };
+
+    let mut evaluator =
+        Evaluator::new(file, &analysis.res).with_consts(analysis.consts.values.clone());
+
+    let Some(def) = evaluator.def_id(&main.alias) else {
+        return Err(Diagnostic::error(
+            "`main` couldn't be resolved",
+            main.alias.span,
+        ));
+    };
+
+    let callee = Value::Fn {
+        def,
+        name: main.alias.alias.clone(),
+        args: Vec::new(),
+    };
+
+    match evaluator.call(callee, Value::unit(), main.alias.span) {
+        Ok(val) => Ok(val),
+        Err(Flow::Error(err)) => Err(err),
+        Err(_) => {
+            let (_, errors) = evaluator.finish();
+            Err(errors.into_iter().next().unwrap_or_else(|| {
+                Diagnostic::error("the program stopped unexpectedly", main.alias.span)
+            }))
+        }
+    }
+}
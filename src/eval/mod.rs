@@ -0,0 +1,11 @@
+//! evaluation of a resolved file, used for constants at compile time and to run programs
+
+mod consts;
+mod evaluator;
+mod interp;
+mod value;
+
+pub use consts::*;
+pub use evaluator::*;
+pub use interp::*;
+pub use value::*;
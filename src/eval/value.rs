@@ -0,0 +1,92 @@
+use crate::sema::DefId;
+use std::{
+    cell::RefCell,
+    fmt::{self, Display, Formatter},
+    rc::Rc,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Chr(char),
+    Str(String),
+    /// `()` is the struct without fields, a union value is a struct with one field
+    Struct(Vec<(String, Value)>),
+    /// a reference shares the slot it points to, so writes through it are seen by the owner
+    Ref(Rc<RefCell<Value>>),
+    /// a fn and the arguments it has been given so far, methods take their receiver first
+    Fn {
+        def: DefId,
+        name: String,
+        args: Vec<Value>,
+    },
+}
+
+impl Value {
+    pub fn unit() -> Self {
+        Self::Struct(Vec::new())
+    }
+
+    pub fn is_unit(&self) -> bool {
+        matches!(self, Self::Struct(fields) if fields.is_empty())
+    }
+
+    /// the value of a field, fields are read through references
+    pub fn field(&self, name: &str) -> Option<Value> {
+        match self {
+            Self::Struct(fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, val)| val.clone()),
+            Self::Ref(val) => val.borrow().field(name),
+            _ => None,
+        }
+    }
+
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut Value> {
+        match self {
+            Self::Struct(fields) => fields
+                .iter_mut()
+                .find(|(field, _)| field == name)
+                .map(|(_, val)| val),
+            _ => None,
+        }
+    }
+
+    /// the value as it is written into an interpolated string, strings aren't quoted
+    pub fn to_text(&self) -> String {
+        match self {
+            Self::Str(val) => val.clone(),
+            Self::Chr(val) => val.to_string(),
+            val => val.to_string(),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(val) => write!(f, "{val}"),
+            Self::Int(val) => write!(f, "{val}"),
+            Self::Float(val) => write!(f, "{val:?}"),
+            Self::Chr(val) => write!(f, "{val:?}"),
+            Self::Str(val) => write!(f, "{val:?}"),
+            Self::Struct(fields) => {
+                f.write_str("(")?;
+                for (i, (name, val)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+
+                    write!(f, "{name} = {val}")?;
+                }
+
+                f.write_str(")")
+            }
+            Self::Ref(val) => write!(f, "{}&", val.borrow()),
+            Self::Fn { name, .. } => write!(f, "fn {name}"),
+        }
+    }
+}
@@ -0,0 +1,505 @@
+use crate::{
+    ast::AstBuildResult,
+    lexer::TokenKind,
+    parser::Parser,
+    syntax::{NodeKind, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken},
+};
+
+const MAX_WIDTH: usize = 100;
+const TAB_WIDTH: usize = 4;
+
+/// formats a whole source file. comments and single blank lines between items are kept,
+/// everything else is laid out canonically so formatting twice gives the same result
+pub fn format_src(src: &str) -> AstBuildResult<String> {
+    let parse = Parser::parse(src)?;
+    let mut printer = Printer::new(false);
+    printer.node(&parse.syntax());
+
+    Ok(printer.finish())
+}
+
+fn token_kind(element: &SyntaxElement) -> Option<TokenKind> {
+    match element.kind() {
+        SyntaxKind::Token(kind) => Some(kind),
+        SyntaxKind::Node(_) => None,
+    }
+}
+
+fn node_kind(element: &SyntaxElement) -> Option<NodeKind> {
+    match element.kind() {
+        SyntaxKind::Node(kind) => Some(kind),
+        SyntaxKind::Token(_) => None,
+    }
+}
+
+fn has_comments(node: &SyntaxNode) -> bool {
+    node.descendants_with_tokens().any(|element| {
+        matches!(
+            token_kind(&element),
+            Some(TokenKind::Comment | TokenKind::BlockComment)
+        )
+    })
+}
+
+/// whether a block only holds an expression, and isn't the body of a fn. such blocks are kept on
+/// one line when they fit
+fn is_short_block(node: &SyntaxNode) -> bool {
+    let in_fn = node.parent().is_some_and(|parent| {
+        matches!(
+            parent.kind(),
+            SyntaxKind::Node(NodeKind::FnDecl | NodeKind::IfaceFnItem)
+        )
+    });
+
+    let has_stmts = node.children().nth(1).is_some()
+        || node
+            .children_with_tokens()
+            .any(|element| token_kind(&element) == Some(TokenKind::Semi));
+
+    !in_fn && !has_stmts && !has_comments(node)
+}
+
+/// whether a block is the body of a `T::{x}` cast
+fn is_cast_body(node: &SyntaxNode) -> bool {
+    let mut prev = node.prev_sibling_or_token();
+    while let Some(element) = prev.take() {
+        match element.kind() {
+            kind if kind.is_trivia() => prev = element.prev_sibling_or_token(),
+            kind => return kind == SyntaxKind::Token(TokenKind::ColonColon),
+        }
+    }
+
+    false
+}
+
+/// whether there is whitespace between two sibling elements of an inline node
+fn inline_space(parent: NodeKind, prev: &SyntaxElement, next: &SyntaxElement) -> bool {
+    if matches!(
+        parent,
+        NodeKind::UnaryExpr | NodeKind::FieldVis | NodeKind::Alias
+    ) {
+        return false;
+    }
+
+    let tight = |element: &SyntaxElement| {
+        matches!(
+            token_kind(element),
+            Some(TokenKind::ColonColon | TokenKind::Dot)
+        )
+    };
+
+    if tight(prev) || tight(next) {
+        return false;
+    }
+
+    !matches!(token_kind(next), Some(TokenKind::Colon))
+        && !matches!(node_kind(next), Some(NodeKind::Bounds))
+}
+
+struct Printer {
+    out: String,
+    indent: usize,
+    /// newlines in the source since the last printed token
+    newlines: usize,
+    need_newline: bool,
+    blank: bool,
+    space: bool,
+    /// the last print opened a block, blank lines are never kept directly after it
+    at_open: bool,
+    after_block_comment: bool,
+    /// a flat printer fails instead of breaking lines
+    flat: bool,
+    failed: bool,
+    /// the `if` chains that didn't fit on one line, every block in them is broken
+    broken_ifs: Vec<SyntaxNode>,
+}
+
+impl Printer {
+    fn new(flat: bool) -> Self {
+        Self {
+            out: String::new(),
+            indent: 0,
+            newlines: 0,
+            need_newline: false,
+            blank: false,
+            space: false,
+            at_open: true,
+            after_block_comment: false,
+            flat,
+            failed: false,
+            broken_ifs: Vec::new(),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+
+        self.out
+    }
+
+    fn column(&self) -> usize {
+        let line = self.out.rsplit('\n').next().unwrap_or_default();
+        line.chars()
+            .map(|ch| if ch == '\t' { TAB_WIDTH } else { 1 })
+            .sum()
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.need_newline && !self.out.is_empty() {
+            if self.flat {
+                self.failed = true;
+            }
+
+            self.out.push('\n');
+            if self.blank && !self.at_open {
+                self.out.push('\n');
+            }
+
+            self.out.push_str(&"\t".repeat(self.indent));
+        } else if self.space && !self.out.is_empty() && !self.out.ends_with(['\n', '\t']) {
+            self.out.push(' ');
+        }
+
+        self.out.push_str(text);
+        self.need_newline = false;
+        self.blank = false;
+        self.space = false;
+        self.at_open = false;
+        self.after_block_comment = false;
+        self.newlines = 0;
+    }
+
+    /// starts the next item on a new line, keeping a single blank line from the source
+    fn item_break(&mut self) {
+        self.need_newline = true;
+        self.blank |= self.newlines >= 2;
+    }
+
+    fn close_break(&mut self) {
+        self.need_newline = true;
+        self.blank = false;
+    }
+
+    fn trivia(&mut self, token: &SyntaxToken) {
+        let SyntaxKind::Token(kind) = token.kind() else {
+            return;
+        };
+
+        match kind {
+            TokenKind::Whitespace => {
+                let newlines = token.text().matches('\n').count();
+                if self.after_block_comment && newlines > 0 {
+                    self.need_newline = true;
+                }
+
+                self.newlines += newlines;
+            }
+            TokenKind::Comment | TokenKind::BlockComment => {
+                let trailing =
+                    self.newlines == 0 && !self.out.is_empty() && !self.out.ends_with('\n');
+
+                if trailing {
+                    self.out.push(' ');
+                    self.out.push_str(token.text());
+                    self.newlines = 0;
+                } else {
+                    self.item_break();
+                    self.write(token.text());
+                }
+
+                match kind {
+                    TokenKind::Comment => self.need_newline = true,
+                    _ => {
+                        self.space = true;
+                        self.after_block_comment = true;
+                    }
+                }
+
+                if self.flat && kind == TokenKind::Comment {
+                    self.failed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn node(&mut self, node: &SyntaxNode) {
+        let SyntaxKind::Node(kind) = node.kind() else {
+            return;
+        };
+
+        match kind {
+            NodeKind::Src => self.items(node, None),
+            NodeKind::Block => self.block(node),
+            NodeKind::IfaceDecl => self.iface(node),
+            NodeKind::IfStmt => self.if_stmt(node),
+            NodeKind::StructExpr => self.struct_expr(node),
+            NodeKind::DocComment => self.doc(node),
+            // interpolated strings are printed as they are written
+            NodeKind::InterpolatedStr => self.write(&node.to_string()),
+            _ => self.inline(node, kind),
+        }
+    }
+
+    fn element(&mut self, element: &SyntaxElement) {
+        match element {
+            SyntaxElement::Node(node) => self.node(node),
+            SyntaxElement::Token(token) => self.write(token.text()),
+        }
+    }
+
+    fn inline(&mut self, node: &SyntaxNode, kind: NodeKind) {
+        let mut prev: Option<SyntaxElement> = None;
+
+        for element in node.children_with_tokens() {
+            if let SyntaxElement::Token(token) = &element {
+                if token.kind().is_trivia() {
+                    self.trivia(token);
+                    continue;
+                }
+            }
+
+            if let Some(prev) = &prev {
+                match node_kind(prev) {
+                    Some(NodeKind::DocComment) => self.close_break(),
+                    _ => self.space |= inline_space(kind, prev, &element),
+                }
+            }
+
+            self.element(&element);
+            prev = Some(element);
+        }
+    }
+
+    fn doc(&mut self, node: &SyntaxNode) {
+        for element in node.children_with_tokens() {
+            let SyntaxElement::Token(token) = element else {
+                continue;
+            };
+
+            match token.kind() {
+                SyntaxKind::Token(TokenKind::DocComment) => {
+                    self.write(token.text());
+                    self.close_break();
+                }
+                _ => self.trivia(&token),
+            }
+        }
+    }
+
+    /// the items of a source file or a block, each on its own line. `open` is the opening delimiter
+    /// of a block, statements in blocks always end with a ';' and top level decls only when they
+    /// end with an expression
+    fn items(&mut self, node: &SyntaxNode, open: Option<TokenKind>) {
+        let empty = node.children().next().is_none() && !has_comments(node);
+
+        for element in node.children_with_tokens() {
+            match element {
+                SyntaxElement::Token(token) => match token.kind() {
+                    SyntaxKind::Token(TokenKind::LBrace) if open.is_some() => {
+                        self.write("{");
+                        self.indent += 1;
+                        self.at_open = true;
+                    }
+                    SyntaxKind::Token(TokenKind::RBrace) => {
+                        self.indent -= 1;
+                        if !empty {
+                            self.close_break();
+                        }
+
+                        self.write("}");
+                    }
+                    SyntaxKind::Token(TokenKind::Semi) if open.is_some() => self.write(";"),
+                    SyntaxKind::Token(TokenKind::Semi) => {}
+                    _ => self.trivia(&token),
+                },
+                SyntaxElement::Node(child) => {
+                    self.item_break();
+                    self.node(&child);
+
+                    if open.is_none() && child.kind() == SyntaxKind::Node(NodeKind::AliasDecl) {
+                        self.write(";");
+                    }
+                }
+            }
+        }
+    }
+
+    /// an `if` chain is kept on one line when every block in it is short and it fits, otherwise
+    /// all of its blocks are broken
+    fn if_stmt(&mut self, node: &SyntaxNode) {
+        let in_broken_chain = node
+            .parent()
+            .is_some_and(|parent| self.broken_ifs.contains(&parent));
+
+        if !self.flat && !in_broken_chain && !has_comments(node) {
+            let mut printer = Printer::new(true);
+            printer.inline(node, NodeKind::IfStmt);
+
+            let fits = self.column() + printer.out.chars().count() < MAX_WIDTH;
+            if !printer.failed && !printer.out.contains('\n') && fits {
+                self.write(&printer.out);
+                return;
+            }
+        }
+
+        if self.flat {
+            return self.inline(node, NodeKind::IfStmt);
+        }
+
+        self.broken_ifs.push(node.clone());
+        self.inline(node, NodeKind::IfStmt);
+        self.broken_ifs.pop();
+    }
+
+    /// short blocks are written as `{ x }` when they fit on the line, and cast bodies as `{x}`
+    fn block(&mut self, node: &SyntaxNode) {
+        let in_broken_if = node
+            .parent()
+            .is_some_and(|parent| self.broken_ifs.contains(&parent));
+
+        if is_short_block(node) && !in_broken_if {
+            let inner_space = !is_cast_body(node);
+            let mut printer = Printer::new(true);
+            printer.write("{");
+
+            if let Some(expr) = node.children().next() {
+                printer.space = inner_space;
+                printer.node(&expr);
+                printer.space = inner_space;
+            }
+
+            printer.write("}");
+
+            let fits = self.column() + printer.out.chars().count() < MAX_WIDTH;
+            if !printer.failed && !printer.out.contains('\n') && (fits || self.flat) {
+                self.write(&printer.out);
+                return;
+            }
+        }
+
+        self.items(node, Some(TokenKind::LBrace));
+    }
+
+    fn iface(&mut self, node: &SyntaxNode) {
+        let empty = !node.children().any(|child| {
+            matches!(
+                child.kind(),
+                SyntaxKind::Node(NodeKind::IfaceAliasItem | NodeKind::IfaceFnItem)
+            )
+        }) && !has_comments(node);
+
+        let mut prev: Option<SyntaxElement> = None;
+        let mut in_body = false;
+
+        for element in node.children_with_tokens() {
+            match (&element, element.kind()) {
+                (SyntaxElement::Token(token), kind) if kind.is_trivia() => self.trivia(token),
+                (_, SyntaxKind::Token(TokenKind::LBrace)) => {
+                    self.space = true;
+                    self.write("{");
+                    self.indent += 1;
+                    self.at_open = true;
+                    in_body = true;
+                }
+                (_, SyntaxKind::Token(TokenKind::RBrace)) => {
+                    self.indent -= 1;
+                    if !empty {
+                        self.close_break();
+                    }
+
+                    self.write("}");
+                }
+                (_, SyntaxKind::Token(TokenKind::Semi)) => {}
+                (SyntaxElement::Node(item), _) if in_body => {
+                    self.item_break();
+                    self.node(item);
+
+                    let has_body = item
+                        .children()
+                        .any(|child| child.kind() == SyntaxKind::Node(NodeKind::Block));
+
+                    if !has_body {
+                        self.write(";");
+                    }
+                }
+                _ => {
+                    if let Some(prev) = &prev {
+                        match node_kind(prev) {
+                            Some(NodeKind::DocComment) => self.close_break(),
+                            _ => self.space = true,
+                        }
+                    }
+
+                    self.element(&element);
+                    prev = Some(element);
+                }
+            }
+        }
+    }
+
+    fn struct_expr(&mut self, node: &SyntaxNode) {
+        if !self.flat && !has_comments(node) {
+            let mut printer = Printer::new(true);
+            printer.struct_items(node, false);
+
+            let fits = self.column() + printer.out.chars().count() < MAX_WIDTH;
+            if !printer.failed && !printer.out.contains('\n') && fits {
+                self.write(&printer.out);
+                return;
+            }
+        }
+
+        self.struct_items(node, !self.flat);
+    }
+
+    fn struct_items(&mut self, node: &SyntaxNode, multi_line: bool) {
+        let mut first = true;
+
+        for element in node.children_with_tokens() {
+            match (&element, element.kind()) {
+                (SyntaxElement::Token(token), kind) if kind.is_trivia() => self.trivia(token),
+                (_, SyntaxKind::Token(TokenKind::LParen)) => {
+                    self.write("(");
+                    if multi_line {
+                        self.indent += 1;
+                        self.at_open = true;
+                    }
+                }
+                (_, SyntaxKind::Token(TokenKind::RParen)) => {
+                    if multi_line {
+                        self.indent -= 1;
+                        self.close_break();
+                    }
+
+                    self.write(")");
+                }
+                (_, SyntaxKind::Token(TokenKind::Comma)) => {}
+                (_, SyntaxKind::Token(TokenKind::Star)) => {
+                    self.space = true;
+                    self.write("*");
+
+                    if multi_line {
+                        self.write(",");
+                    }
+                }
+                _ => {
+                    match multi_line {
+                        true => self.item_break(),
+                        false if !first => self.write(", "),
+                        false => {}
+                    }
+
+                    self.element(&element);
+                    first = false;
+
+                    if multi_line && node_kind(&element) == Some(NodeKind::StructField) {
+                        self.write(",");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,675 @@
+use crate::{
+    ast::{AstBuildError, AstBuildErrorKind},
+    span::Span,
+};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum TokenKind {
+    Whitespace,
+    Comment,
+    BlockComment,
+    DocComment,
+
+    Alias,
+
+    KwTrue,
+    KwFalse,
+    KwLet,
+    KwVar,
+    KwConst,
+    KwType,
+    KwFn,
+    KwIface,
+    KwPub,
+    KwIf,
+    KwElse,
+    KwWhile,
+    KwReturn,
+    KwDefer,
+    KwContinue,
+    KwBreak,
+
+    Int,
+    Float,
+    Chr,
+    Str,
+    RawStr,
+    MlStr,
+
+    InterpStrStart,
+    InterpStrText,
+    InterpExprStart,
+    InterpExprEnd,
+    InterpStrEnd,
+
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Semi,
+    Comma,
+    Colon,
+    Eq,
+    Star,
+    ColonColon,
+    Dot,
+    Arrow,
+    Gt,
+    Lt,
+    Amp,
+    Question,
+
+    Error,
+}
+
+/// reserved words can never be used as an alias
+pub const KEYWORDS: &[(&str, TokenKind)] = &[
+    ("true", TokenKind::KwTrue),
+    ("false", TokenKind::KwFalse),
+    ("let", TokenKind::KwLet),
+    ("var", TokenKind::KwVar),
+    ("const", TokenKind::KwConst),
+    ("type", TokenKind::KwType),
+    ("fn", TokenKind::KwFn),
+    ("iface", TokenKind::KwIface),
+    ("pub", TokenKind::KwPub),
+    ("if", TokenKind::KwIf),
+    ("else", TokenKind::KwElse),
+    ("while", TokenKind::KwWhile),
+    ("return", TokenKind::KwReturn),
+    ("defer", TokenKind::KwDefer),
+    ("continue", TokenKind::KwContinue),
+    ("break", TokenKind::KwBreak),
+];
+
+impl TokenKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(self, Self::Whitespace | Self::Comment | Self::BlockComment)
+    }
+
+    pub fn keyword(self) -> Option<&'static str> {
+        KEYWORDS
+            .iter()
+            .find(|(_, kind)| *kind == self)
+            .map(|(kw, _)| *kw)
+    }
+
+    fn punct(self) -> Option<&'static str> {
+        let punct = match self {
+            Self::InterpStrStart => "$\"",
+            Self::InterpExprStart | Self::LBrace => "{",
+            Self::InterpExprEnd | Self::RBrace => "}",
+            Self::InterpStrEnd => "\"",
+            Self::LParen => "(",
+            Self::RParen => ")",
+            Self::Semi => ";",
+            Self::Comma => ",",
+            Self::Colon => ":",
+            Self::Eq => "=",
+            Self::Star => "*",
+            Self::ColonColon => "::",
+            Self::Dot => ".",
+            Self::Arrow => "->",
+            Self::Gt => ">",
+            Self::Lt => "<",
+            Self::Amp => "&",
+            Self::Question => "?",
+            _ => return None,
+        };
+
+        Some(punct)
+    }
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(text) = self.keyword().or(self.punct()) {
+            return write!(f, "`{text}`");
+        }
+
+        let desc = match self {
+            Self::Whitespace => "whitespace",
+            Self::Comment | Self::BlockComment => "comment",
+            Self::DocComment => "doc comment",
+            Self::Alias => "alias",
+            Self::Int => "integer literal",
+            Self::Float => "float literal",
+            Self::Chr => "character literal",
+            Self::Str | Self::RawStr | Self::MlStr => "string literal",
+            Self::InterpStrText => "string literal",
+            Self::Error => "invalid token",
+            _ => unreachable!(),
+        };
+
+        f.write_str(desc)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// the value of the character following a backslash in a string or character literal
+pub fn unescape(ch: char) -> Option<char> {
+    let val = match ch {
+        't' => '\t',
+        'r' => '\r',
+        'n' => '\n',
+        '\'' => '\'',
+        '"' => '"',
+        '\\' => '\\',
+        '{' => '{',
+        '}' => '}',
+        _ => return None,
+    };
+
+    Some(val)
+}
+
+pub fn is_alias_start(ch: char) -> bool {
+    unicode_ident::is_xid_start(ch)
+}
+
+pub fn is_alias_continue(ch: char) -> bool {
+    unicode_ident::is_xid_continue(ch)
+}
+
+enum Mode {
+    /// the expression inside of an interpolated string, `depth` counts unclosed braces
+    Interp { depth: usize },
+    /// the text of an interpolated string that started at `start`
+    InterpStr { start: usize },
+}
+
+pub struct Lexer<'i> {
+    src: &'i str,
+    pos: usize,
+    modes: Vec<Mode>,
+    errors: Vec<AstBuildError>,
+}
+
+impl<'i> Lexer<'i> {
+    pub fn new(src: &'i str) -> Self {
+        Self {
+            src,
+            pos: 0,
+            modes: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// splits the source into tokens. every byte of the input is covered by exactly one token,
+    /// malformed input is reported in the returned errors
+    pub fn tokenize(src: &'i str) -> (Vec<Token>, Vec<AstBuildError>) {
+        let mut lexer = Self::new(src);
+        let mut tokens = Vec::new();
+
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+
+        for mode in lexer.modes.drain(..) {
+            if let Mode::InterpStr { start } = mode {
+                lexer.errors.push(AstBuildError {
+                    kind: AstBuildErrorKind::UnterminatedStr {},
+                    span: Span::new(start, start + 2),
+                });
+            }
+        }
+
+        (tokens, lexer.errors)
+    }
+
+    fn rest(&self) -> &'i str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn bump_while(&mut self, mut pred: impl FnMut(char) -> bool) {
+        while self.peek().is_some_and(&mut pred) {
+            self.bump();
+        }
+    }
+
+    fn error(&mut self, kind: AstBuildErrorKind, span: Span) {
+        self.errors.push(AstBuildError { kind, span });
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let start = self.pos;
+        let kind = match self.modes.last() {
+            Some(Mode::InterpStr { .. }) => self.interp_str_text()?,
+            _ => self.code()?,
+        };
+
+        Some(Token {
+            kind,
+            span: Span::new(start, self.pos),
+        })
+    }
+
+    fn code(&mut self) -> Option<TokenKind> {
+        let start = self.pos;
+        let ch = self.peek()?;
+
+        let kind = match ch {
+            ' ' | '\t' | '\n' | '\r' => {
+                self.bump_while(|ch| matches!(ch, ' ' | '\t' | '\n' | '\r'));
+                TokenKind::Whitespace
+            }
+            '#' => self.comment(),
+            '"' if self.rest().starts_with("\"\"\"") => self.ml_str(),
+            '"' => self.str(),
+            '\'' => self.chr(),
+            'r' if self.is_raw_str() => self.raw_str(),
+            '$' if self.peek_nth(1) == Some('"') => {
+                self.pos += 2;
+                self.modes.push(Mode::InterpStr { start });
+                TokenKind::InterpStrStart
+            }
+            '0'..='9' => self.num(),
+            '+' | '-' if self.peek_nth(1).is_some_and(|ch| ch.is_ascii_digit()) => self.num(),
+            '-' if self.peek_nth(1) == Some('>') => {
+                self.pos += 2;
+                TokenKind::Arrow
+            }
+            ':' if self.peek_nth(1) == Some(':') => {
+                self.pos += 2;
+                TokenKind::ColonColon
+            }
+            ch if ch == '_' || is_alias_start(ch) => self.alias(),
+            _ => {
+                self.bump();
+                self.punct(ch, start)
+            }
+        };
+
+        Some(kind)
+    }
+
+    fn punct(&mut self, ch: char, start: usize) -> TokenKind {
+        match ch {
+            '{' => {
+                if let Some(Mode::Interp { depth }) = self.modes.last_mut() {
+                    *depth += 1;
+                }
+
+                TokenKind::LBrace
+            }
+            '}' => match self.modes.last_mut() {
+                Some(Mode::Interp { depth: 0 }) => {
+                    self.modes.pop();
+                    TokenKind::InterpExprEnd
+                }
+                Some(Mode::Interp { depth }) => {
+                    *depth -= 1;
+                    TokenKind::RBrace
+                }
+                _ => TokenKind::RBrace,
+            },
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            ';' => TokenKind::Semi,
+            ',' => TokenKind::Comma,
+            ':' => TokenKind::Colon,
+            '=' => TokenKind::Eq,
+            '*' => TokenKind::Star,
+            '.' => TokenKind::Dot,
+            '>' => TokenKind::Gt,
+            '<' => TokenKind::Lt,
+            '&' => TokenKind::Amp,
+            '?' => TokenKind::Question,
+            _ => {
+                self.error(
+                    AstBuildErrorKind::UnexpectedChar {},
+                    Span::new(start, self.pos),
+                );
+                TokenKind::Error
+            }
+        }
+    }
+
+    /// '##' comments are kept and attached to the following item, '###' is a regular comment.
+    /// block comments can be nested, '#[ #[ ]# ]#' is a single comment
+    fn comment(&mut self) -> TokenKind {
+        let start = self.pos;
+
+        if self.rest().starts_with("#[") {
+            let mut depth = 0usize;
+
+            loop {
+                if self.rest().starts_with("#[") {
+                    self.pos += 2;
+                    depth += 1;
+                } else if self.rest().starts_with("]#") {
+                    self.pos += 2;
+                    depth -= 1;
+
+                    if depth == 0 {
+                        break;
+                    }
+                } else if self.bump().is_none() {
+                    self.error(
+                        AstBuildErrorKind::UnterminatedBlockComment {},
+                        Span::new(start, start + 2),
+                    );
+                    break;
+                }
+            }
+
+            return TokenKind::BlockComment;
+        }
+
+        let doc = self.rest().starts_with("##") && !self.rest().starts_with("###");
+        self.bump_while(|ch| ch != '\n' && ch != '\r');
+
+        match doc {
+            true => TokenKind::DocComment,
+            false => TokenKind::Comment,
+        }
+    }
+
+    fn alias(&mut self) -> TokenKind {
+        let start = self.pos;
+
+        // identifiers follow the unicode XID rules with any number of leading underscores. '-' is
+        // not an identifier character so that 'a-1' and 'a->b' are never read as a single name
+        self.bump_while(|ch| ch == '_');
+        if !self.peek().is_some_and(is_alias_start) {
+            self.error(
+                AstBuildErrorKind::UnexpectedChar {},
+                Span::new(start, self.pos),
+            );
+            return TokenKind::Error;
+        }
+
+        self.bump_while(is_alias_continue);
+
+        let text = &self.src[start..self.pos];
+        KEYWORDS
+            .iter()
+            .find(|(kw, _)| *kw == text)
+            .map_or(TokenKind::Alias, |(_, kind)| *kind)
+    }
+
+    fn num(&mut self) -> TokenKind {
+        let start = self.pos;
+
+        if matches!(self.peek(), Some('+' | '-')) {
+            self.bump();
+        }
+
+        let radix = match (self.peek(), self.peek_nth(1)) {
+            (Some('0'), Some('x')) => 16,
+            (Some('0'), Some('o')) => 8,
+            (Some('0'), Some('b')) => 2,
+            (Some('0'), Some('d')) => 10,
+            _ => 0,
+        };
+
+        let radix = match radix {
+            0 => 10,
+            radix => {
+                self.pos += 2;
+                radix
+            }
+        };
+
+        if !self.digits(radix) {
+            self.error(
+                AstBuildErrorKind::ExpectedDigits {},
+                Span::new(start, self.pos),
+            );
+            return TokenKind::Error;
+        }
+
+        if self.peek() != Some('.') {
+            return TokenKind::Int;
+        }
+
+        self.bump();
+        self.digits(radix);
+        TokenKind::Float
+    }
+
+    /// digits may be separated with '_' after the first one
+    fn digits(&mut self, radix: u32) -> bool {
+        if !self.peek().is_some_and(|ch| ch.is_digit(radix)) {
+            return false;
+        }
+
+        self.bump_while(|ch| ch.is_digit(radix) || ch == '_');
+        true
+    }
+
+    fn escape(&mut self) {
+        let start = self.pos;
+        self.bump();
+
+        if self.bump().and_then(unescape).is_none() {
+            self.error(
+                AstBuildErrorKind::InvalidEscape {},
+                Span::new(start, self.pos),
+            );
+        }
+    }
+
+    fn chr(&mut self) -> TokenKind {
+        let start = self.pos;
+        self.bump();
+
+        match self.peek() {
+            Some('\\') => self.escape(),
+            Some('\'') | None => {}
+            Some(_) => {
+                self.bump();
+            }
+        }
+
+        if self.peek() == Some('\'') && self.pos > start + 1 {
+            self.bump();
+            return TokenKind::Chr;
+        }
+
+        // recover at the closing quote if it is on the same line
+        let line_end = self.rest().find(['\n', '\r']).unwrap_or(self.rest().len());
+        match self.rest()[..line_end].find('\'') {
+            Some(end) => {
+                self.pos += end + 1;
+                self.error(AstBuildErrorKind::InvalidChr {}, Span::new(start, self.pos));
+            }
+            None => self.error(
+                AstBuildErrorKind::UnterminatedChr {},
+                Span::new(start, start + 1),
+            ),
+        }
+
+        TokenKind::Chr
+    }
+
+    fn str(&mut self) -> TokenKind {
+        let start = self.pos;
+        self.bump();
+
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.bump();
+                    break;
+                }
+                Some('\\') => self.escape(),
+                Some(_) => {
+                    self.bump();
+                }
+                None => {
+                    self.error(
+                        AstBuildErrorKind::UnterminatedStr {},
+                        Span::new(start, start + 1),
+                    );
+                    break;
+                }
+            }
+        }
+
+        TokenKind::Str
+    }
+
+    fn is_raw_str(&self) -> bool {
+        self.rest()[1..].trim_start_matches('#').starts_with('"')
+    }
+
+    /// r"..." or r#"..."#, the closing quote must be followed by the same number of '#'
+    fn raw_str(&mut self) -> TokenKind {
+        let start = self.pos;
+        self.bump();
+
+        let hashes = self.rest().len() - self.rest().trim_start_matches('#').len();
+        self.pos += hashes + 1;
+
+        let close = format!("\"{}", "#".repeat(hashes));
+        match self.rest().find(&close) {
+            Some(end) => self.pos += end + close.len(),
+            None => {
+                self.pos = self.src.len();
+                self.error(
+                    AstBuildErrorKind::UnterminatedStr {},
+                    Span::new(start, start + hashes + 2),
+                );
+            }
+        }
+
+        TokenKind::RawStr
+    }
+
+    /// the opening and closing delimiters must be on their own lines, the indentation of the
+    /// closing delimiter is stripped from every line
+    fn ml_str(&mut self) -> TokenKind {
+        let start = self.pos;
+        self.pos += 3;
+        self.bump_while(|ch| ch == ' ' || ch == '\t');
+
+        let open_newline = self.newline();
+        if !open_newline {
+            self.error(
+                AstBuildErrorKind::MlStrDelim {},
+                Span::new(start, start + 3),
+            );
+        }
+
+        loop {
+            let line_start = self.pos;
+            self.bump_while(|ch| ch == ' ' || ch == '\t');
+
+            if self.rest().starts_with("\"\"\"") {
+                self.pos += 3;
+                return TokenKind::MlStr;
+            }
+
+            loop {
+                match self.peek() {
+                    Some('\\') => self.escape(),
+                    Some('"') if self.rest().starts_with("\"\"\"") => {
+                        if open_newline {
+                            self.error(
+                                AstBuildErrorKind::MlStrDelim {},
+                                Span::new(self.pos, self.pos + 3),
+                            );
+                        }
+
+                        self.pos += 3;
+                        return TokenKind::MlStr;
+                    }
+                    Some('\n' | '\r') => break,
+                    Some(_) => {
+                        self.bump();
+                    }
+                    None => {
+                        self.error(
+                            AstBuildErrorKind::UnterminatedStr {},
+                            Span::new(start, start + 3),
+                        );
+                        return TokenKind::MlStr;
+                    }
+                }
+            }
+
+            self.newline();
+            debug_assert!(self.pos > line_start);
+        }
+    }
+
+    fn newline(&mut self) -> bool {
+        match self.peek() {
+            Some('\n') => {
+                self.bump();
+                true
+            }
+            Some('\r') => {
+                self.bump();
+                if self.peek() == Some('\n') {
+                    self.bump();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// $"...{expr}...", braces can be escaped with a backslash
+    fn interp_str_text(&mut self) -> Option<TokenKind> {
+        let start = self.pos;
+
+        let kind = match self.peek()? {
+            '"' => {
+                self.bump();
+                self.modes.pop();
+                TokenKind::InterpStrEnd
+            }
+            '{' => {
+                self.bump();
+                self.modes.push(Mode::Interp { depth: 0 });
+                TokenKind::InterpExprStart
+            }
+            '}' => {
+                self.bump();
+                self.error(
+                    AstBuildErrorKind::UnescapedBrace {},
+                    Span::new(start, self.pos),
+                );
+                TokenKind::Error
+            }
+            _ => {
+                while let Some(ch) = self.peek() {
+                    match ch {
+                        '"' | '{' | '}' => break,
+                        '\\' => self.escape(),
+                        _ => {
+                            self.bump();
+                        }
+                    }
+                }
+
+                TokenKind::InterpStrText
+            }
+        };
+
+        Some(kind)
+    }
+}
@@ -0,0 +1,39 @@
+pub mod ast;
+pub mod diagnostic;
+pub mod eval;
+pub mod fmt;
+pub mod lexer;
+pub mod parser;
+pub mod reparse;
+pub mod repl;
+pub mod sema;
+pub mod span;
+pub mod syntax;
+
+use ast::SourceFile;
+use diagnostic::Diagnostic;
+use lexer::Lexer;
+use parser::Parser;
+
+/// parses a whole source file. the parser stops at the first error, but every error found by the
+/// lexer is reported
+pub fn parse_source(src: &str) -> Result<SourceFile, Vec<Diagnostic>> {
+    Parser::parse(src).map(|parse| parse.file).map_err(|err| {
+        let (_, errors) = Lexer::tokenize(src);
+        match errors.is_empty() {
+            true => vec![err.into()],
+            false => errors.into_iter().map(Into::into).collect(),
+        }
+    })
+}
+
+/// parses a whole source file and runs the semantic checks over it
+pub fn check_source(src: &str) -> Result<SourceFile, Vec<Diagnostic>> {
+    let file = parse_source(src)?;
+    let errors = sema::check(&file);
+
+    match errors.is_empty() {
+        true => Ok(file),
+        false => Err(errors),
+    }
+}
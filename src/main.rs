@@ -1,13 +1,416 @@
-pub mod ast;
-pub mod parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rename_me::{
+    ast::{self, SourceFile},
+    diagnostic::{Diagnostic, Severity},
+    eval,
+    fmt::format_src,
+    parse_source,
+    repl::{self, Session},
+    sema::{self, Analysis, DefId, DefKind},
+};
+use std::{
+    env, fs,
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
-use ast::{AstNode, Decl};
-use parser::{Parser, Rule};
-use pest::Parser as _;
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  success
+  1  a file has errors, or isn't formatted when checking formatting
+  2  invalid arguments or a file couldn't be read";
 
-fn main() {
-    let mut pairs = Parser::parse(Rule::src, include_str!("../tour/01-variables.idk")).unwrap();
-    while let Some(decl) = Decl::parse(&mut pairs).unwrap() {
-        println!("{decl:?}")
+#[derive(Parser)]
+#[command(version, about, after_help = EXIT_CODES)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// when to colour diagnostics
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// how diagnostics are printed
+    #[arg(long, global = true, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// report syntax errors
+    Parse(Files),
+    /// report every error without running anything
+    Check(Files),
+    /// run a program
+    Run {
+        /// the file that holds the program's entry point
+        path: PathBuf,
+    },
+    /// compile to an executable
+    Build(Files),
+    /// format files in place
+    Fmt {
+        /// only report unformatted files, without rewriting them
+        #[arg(long)]
+        check: bool,
+
+        #[command(flatten)]
+        files: Files,
+    },
+    /// print the syntax tree of files, one line per file
+    DumpAst {
+        #[arg(long, value_enum, default_value_t = DumpFormat::Json)]
+        format: DumpFormat,
+
+        #[command(flatten)]
+        files: Files,
+    },
+    /// print the type of every value binding, one line per binding
+    DumpTypes(Files),
+    /// evaluate decls, statements and expressions interactively
+    Repl,
+}
+
+#[derive(Args)]
+struct Files {
+    /// source files, directories are searched recursively for `.idk` files
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MessageFormat {
+    Human,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DumpFormat {
+    Json,
+    Sexpr,
+}
+
+const SRC_EXT: &str = "idk";
+
+/// runs a command over a set of files, reports diagnostics and tracks the exit code
+struct Driver {
+    color: bool,
+    message_format: MessageFormat,
+    status: u8,
+}
+
+impl Driver {
+    fn fail(&mut self, status: u8) {
+        self.status = self.status.max(status);
     }
+
+    fn io_error(&mut self, path: &Path, err: io::Error) {
+        eprintln!("error: {}: {err}", path.display());
+        self.fail(2);
+    }
+
+    fn report(&mut self, path: &Path, src: &str, diagnostics: &[Diagnostic]) {
+        let path = path.display().to_string();
+        for diagnostic in diagnostics {
+            match self.message_format {
+                MessageFormat::Human => eprintln!("{}", diagnostic.render(&path, src, self.color)),
+                MessageFormat::Json => eprintln!("{}", diagnostic.to_json(&path, src)),
+            }
+
+            if diagnostic.severity == Severity::Error {
+                self.fail(1);
+            }
+        }
+    }
+
+    /// expands directories into the source files they contain, in a stable order
+    fn files(&mut self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for path in paths {
+            match path.is_dir() {
+                true => self.walk(path, &mut files),
+                false => files.push(path.clone()),
+            }
+        }
+
+        files
+    }
+
+    fn walk(&mut self, dir: &Path, files: &mut Vec<PathBuf>) {
+        let entries = fs::read_dir(dir).and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()
+        });
+
+        let mut paths = match entries {
+            Ok(paths) => paths,
+            Err(err) => return self.io_error(dir, err),
+        };
+
+        paths.sort();
+        for path in paths {
+            if path.is_dir() {
+                self.walk(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == SRC_EXT) {
+                files.push(path);
+            }
+        }
+    }
+
+    fn read(&mut self, path: &Path) -> Option<String> {
+        fs::read_to_string(path)
+            .map_err(|err| self.io_error(path, err))
+            .ok()
+    }
+
+    fn parse(&mut self, path: &Path) -> Option<(String, SourceFile)> {
+        let src = self.read(path)?;
+        match parse_source(&src) {
+            Ok(mut file) => {
+                file.path = Some(path.to_path_buf());
+                Some((src, file))
+            }
+            Err(diagnostics) => {
+                self.report(path, &src, &diagnostics);
+                None
+            }
+        }
+    }
+
+    /// parses a file and runs the semantic checks over it
+    fn check(&mut self, path: &Path) -> Option<(String, SourceFile)> {
+        let (src, file) = self.parse(path)?;
+        let errors = sema::check(&file);
+        self.report(path, &src, &errors);
+
+        errors.is_empty().then_some((src, file))
+    }
+
+    fn fmt(&mut self, path: &Path, check: bool) {
+        let Some(src) = self.read(path) else {
+            return;
+        };
+
+        let formatted = match format_src(&src) {
+            Ok(formatted) => formatted,
+            Err(err) => return self.report(path, &src, &[err.into()]),
+        };
+
+        if formatted == src {
+            return;
+        }
+
+        if check {
+            println!("{} isn't formatted", path.display());
+            self.fail(1);
+        } else if let Err(err) = fs::write(path, formatted) {
+            self.io_error(path, err);
+        }
+    }
+
+    /// reads input until it is complete or an empty line is entered, then evaluates it
+    fn repl(&mut self) {
+        let mut session = Session::new();
+        let mut lines = io::stdin().lock().lines();
+        let mut input = String::new();
+
+        loop {
+            print!("{}", if input.is_empty() { "> " } else { ". " });
+            io::stdout().flush().ok();
+
+            let line = match lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => return self.io_error(Path::new("<stdin>"), err),
+                None => return println!(),
+            };
+
+            let force = line.trim().is_empty();
+            input.push_str(&line);
+            input.push('\n');
+
+            if input.trim().is_empty() {
+                input.clear();
+                continue;
+            }
+
+            if !force && repl::is_incomplete(&input) {
+                continue;
+            }
+
+            for line in session.eval(&input) {
+                match line {
+                    Ok(line) => println!("{line}"),
+                    Err(diagnostic) => {
+                        eprintln!("{}", diagnostic.render("<repl>", session.src(), self.color))
+                    }
+                }
+            }
+
+            input.clear();
+        }
+    }
+
+    /// checks a file and runs its `main` fn, a value other than `()` returned by `main` is
+    /// printed
+    fn run_file(&mut self, path: &Path) {
+        let Some((src, file)) = self.parse(path) else {
+            return;
+        };
+
+        let analysis = sema::analyze(&file);
+        self.report(path, &src, &analysis.errors);
+        if !analysis.errors.is_empty() {
+            return;
+        }
+
+        match eval::run(&file, &analysis) {
+            Ok(val) if val.is_unit() => {}
+            Ok(val) => println!("{val}"),
+            Err(err) => self.report(path, &src, &[err]),
+        }
+    }
+
+    /// prints one line per file, prefixed with its path when there are several files. output
+    /// stops quietly when stdout is closed, e.g. when piped into `head`
+    fn dump_ast(&mut self, format: DumpFormat, paths: &[PathBuf]) {
+        let paths = self.files(paths);
+        let mut out = io::stdout().lock();
+
+        for path in &paths {
+            let Some((_, file)) = self.parse(path) else {
+                continue;
+            };
+
+            let dump = match format {
+                DumpFormat::Json => ast::to_json(&file.decls),
+                DumpFormat::Sexpr => ast::to_sexpr(&file.decls),
+            };
+
+            let written = match paths.len() {
+                1 => writeln!(out, "{dump}"),
+                _ => writeln!(out, "{}: {dump}", path.display()),
+            };
+
+            match written {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return,
+                Err(err) => return self.io_error(Path::new("<stdout>"), err),
+            }
+        }
+    }
+
+    fn unsupported(&mut self, what: &str) {
+        eprintln!("error: {what} isn't supported yet");
+        self.fail(1);
+    }
+
+    fn run(&mut self, command: Command) {
+        match command {
+            Command::Parse(files) => {
+                for path in self.files(&files.paths) {
+                    self.parse(&path);
+                }
+            }
+            Command::Check(files) => {
+                for path in self.files(&files.paths) {
+                    self.check(&path);
+                }
+            }
+            Command::Run { path } => self.run_file(&path),
+            Command::Build(files) => {
+                let mut checked = true;
+                for path in self.files(&files.paths) {
+                    checked &= self.check(&path).is_some();
+                }
+
+                if checked {
+                    self.unsupported("compiling programs");
+                }
+            }
+            Command::Fmt { check, files } => {
+                for path in self.files(&files.paths) {
+                    self.fmt(&path, check);
+                }
+            }
+            Command::DumpAst { format, files } => self.dump_ast(format, &files.paths),
+            Command::DumpTypes(files) => {
+                for path in self.files(&files.paths) {
+                    let Some((src, file)) = self.parse(&path) else {
+                        continue;
+                    };
+
+                    let analysis = sema::analyze(&file);
+                    self.report(&path, &src, &analysis.errors);
+                    dump_types(&path, &src, &analysis);
+                }
+            }
+            Command::Repl => self.repl(),
+        }
+    }
+}
+
+/// prints `path:line:column: kind name: type` for every binding with a known type, followed by
+/// `= value` for evaluated constants. types are printed even when the file has errors
+fn dump_types(path: &Path, src: &str, analysis: &Analysis) {
+    let mut bindings: Vec<_> = analysis
+        .res
+        .defs
+        .iter()
+        .enumerate()
+        .filter_map(|(id, def)| {
+            let id = DefId(id);
+            let ty = analysis.types.values.get(&id)?;
+            Some((def.span?, id, def, ty))
+        })
+        .collect();
+
+    bindings.sort_by_key(|(span, ..)| span.start);
+    for (span, id, def, ty) in bindings {
+        let kind = match def.kind {
+            DefKind::Const => "const",
+            DefKind::Let => "let",
+            DefKind::Var => "var",
+            DefKind::Fn => "fn",
+            DefKind::Arg | DefKind::SelfArg => "arg",
+            DefKind::Ret => "ret",
+            _ => continue,
+        };
+
+        let (line, col) = span.line_col(src);
+        match analysis.consts.values.get(&id) {
+            Some(val) => println!(
+                "{}:{line}:{col}: {kind} {}: {ty} = {val}",
+                path.display(),
+                def.name
+            ),
+            None => println!("{}:{line}:{col}: {kind} {}: {ty}", path.display(), def.name),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let color = match cli.color {
+        ColorChoice::Auto => io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none(),
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    };
+
+    let mut driver = Driver {
+        color,
+        message_format: cli.message_format,
+        status: 0,
+    };
+
+    driver.run(cli.command);
+    ExitCode::from(driver.status)
 }
@@ -1,38 +1,312 @@
-use lazy_static::lazy_static;
-use pest::{
-    iterators::{Pair, Pairs},
-    pratt_parser::PrattParser,
+use crate::{
+    ast::{AstBuildError, AstBuildErrorKind, AstBuildResult, AstNode, SourceFile},
+    lexer::{Lexer, Token, TokenKind},
+    span::Span,
+    syntax::{NodeKind, SyntaxNode},
 };
-use pest_derive::Parser as PestParser;
-
-#[derive(PestParser)]
-#[grammar = "grammar.pest"]
-pub struct Parser;
-
-lazy_static! {
-    pub static ref PRATT_PARSER: PrattParser<Rule> = {
-        use pest::pratt_parser::{Assoc::*, Op};
-        use Rule::*;
-
-        PrattParser::new()
-            .op(Op::infix(pipe_r, Left) | Op::infix(pipe_l, Right))
-            .op(Op::infix(exec_path, Left))
-            .op(Op::infix(eval_path, Left))
-            .op(Op::postfix(unwrap))
-            .op(Op::postfix(reference) | Op::postfix(dereference))
+use rowan::{Checkpoint, GreenNode, GreenNodeBuilder};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+/// the precedence of an infix operator, operators with a higher precedence bind tighter. postfix
+/// operators bind tighter than every infix operator
+pub fn infix_prec(kind: TokenKind) -> Option<(u8, Assoc)> {
+    let prec = match kind {
+        TokenKind::Arrow => (1, Assoc::Right),
+        TokenKind::Gt => (2, Assoc::Left),
+        TokenKind::Lt => (2, Assoc::Right),
+        TokenKind::Dot => (3, Assoc::Left),
+        TokenKind::ColonColon => (4, Assoc::Left),
+        _ => return None,
     };
+
+    Some(prec)
+}
+
+/// whether doc comments between two tokens document something. decls and iface items take docs
+/// wherever they appear, struct fields when they follow `(` or `,`
+fn documents(prev: Option<TokenKind>, next: TokenKind) -> bool {
+    match next {
+        TokenKind::KwLet
+        | TokenKind::KwVar
+        | TokenKind::KwConst
+        | TokenKind::KwType
+        | TokenKind::KwFn
+        | TokenKind::KwIface => true,
+        TokenKind::KwPub | TokenKind::Alias => {
+            matches!(prev, Some(TokenKind::LParen | TokenKind::Comma))
+        }
+        _ => false,
+    }
+}
+
+/// turns doc comments with nothing to document, e.g. before a statement, a `}` or the end of the
+/// input, into plain comments so they are trivia
+fn demote_stray_docs(tokens: &mut [Token]) {
+    let mut prev = None;
+    let mut docs = Vec::new();
+
+    for i in 0..tokens.len() {
+        match tokens[i].kind {
+            TokenKind::DocComment => docs.push(i),
+            kind if kind.is_trivia() => {}
+            kind => {
+                if !documents(prev, kind) {
+                    for &doc in &docs {
+                        tokens[doc].kind = TokenKind::Comment;
+                    }
+                }
+
+                docs.clear();
+                prev = Some(kind);
+            }
+        }
+    }
+
+    for doc in docs {
+        tokens[doc].kind = TokenKind::Comment;
+    }
+}
+
+/// the result of parsing a whole source file, the syntax tree holds every token including trivia
+/// so its text is always identical to the source
+#[derive(Debug)]
+pub struct Parse {
+    pub green: GreenNode,
+    pub file: SourceFile,
+}
+
+impl Parse {
+    pub fn syntax(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+}
+
+/// the start of a node, both in the syntax tree and in the source
+#[derive(Debug, Clone, Copy)]
+pub struct Marker {
+    checkpoint: Checkpoint,
+    start: Span,
 }
 
-pub trait PairsExt<'i> {
-    fn next_if(&mut self, rule: Rule) -> Option<Pair<Rule>>;
+pub struct Parser<'i> {
+    src: &'i str,
+    /// the position of `src` in the whole source file, every span is relative to the file
+    offset: usize,
+    tokens: Vec<Token>,
+    significant: Vec<usize>,
+    pos: usize,
+    emitted: usize,
+    builder: GreenNodeBuilder<'static>,
 }
 
-impl<'i> PairsExt<'i> for Pairs<'i, Rule> {
-    fn next_if(&mut self, rule: Rule) -> Option<Pair<Rule>> {
-        if self.peek().is_some_and(|pair| pair.as_rule() == rule) {
-            self.next()
-        } else {
-            None
+impl<'i> Parser<'i> {
+    pub fn new(src: &'i str) -> AstBuildResult<Self> {
+        Self::new_at(src, 0)
+    }
+
+    /// a parser for a slice of a source file that starts at `offset`
+    pub fn new_at(src: &'i str, offset: usize) -> AstBuildResult<Self> {
+        let (mut tokens, errors) = Lexer::tokenize(src);
+        if let Some(mut err) = errors.into_iter().next() {
+            err.span = Span::new(err.span.start + offset, err.span.end + offset);
+            return Err(err);
+        }
+
+        for token in &mut tokens {
+            token.span = Span::new(token.span.start + offset, token.span.end + offset);
+        }
+
+        demote_stray_docs(&mut tokens);
+
+        let significant = (0..tokens.len())
+            .filter(|i| !tokens[*i].kind.is_trivia())
+            .collect();
+
+        Ok(Self {
+            src,
+            offset,
+            tokens,
+            significant,
+            pos: 0,
+            emitted: 0,
+            builder: GreenNodeBuilder::new(),
+        })
+    }
+
+    /// parses a whole source file
+    pub fn parse(src: &'i str) -> AstBuildResult<Parse> {
+        let mut parser = Self::new(src)?;
+        let file = SourceFile::expect(&mut parser)?;
+
+        Ok(Parse {
+            green: parser.builder.finish(),
+            file,
+        })
+    }
+
+    /// parses `src`, which starts at `offset` in the source file, as exactly one node without
+    /// surrounding trivia. used to reparse a subtree
+    pub fn parse_exact<N: AstNode>(src: &'i str, offset: usize) -> Option<(GreenNode, N)> {
+        let mut parser = Self::new_at(src, offset).ok()?;
+        let trimmed = [parser.tokens.first()?, parser.tokens.last()?]
+            .iter()
+            .all(|token| !token.kind.is_trivia());
+
+        if !trimmed {
+            return None;
+        }
+
+        let node = N::parse(&mut parser).ok()??;
+        if parser.emitted != parser.tokens.len() {
+            return None;
+        }
+
+        Some((parser.builder.finish(), node))
+    }
+
+    pub fn src(&self) -> &'i str {
+        self.src
+    }
+
+    pub fn text(&self, token: &Token) -> &'i str {
+        &self.src[token.span.start - self.offset..token.span.end - self.offset]
+    }
+
+    pub fn peek(&self) -> Option<TokenKind> {
+        self.peek_nth(0)
+    }
+
+    pub fn peek_nth(&self, n: usize) -> Option<TokenKind> {
+        self.token_nth(n).map(|token| token.kind)
+    }
+
+    pub fn peek_text_nth(&self, n: usize) -> Option<&'i str> {
+        self.token_nth(n).map(|token| self.text(token))
+    }
+
+    pub fn peek_token(&self) -> Option<&Token> {
+        self.token_nth(0)
+    }
+
+    fn token_nth(&self, n: usize) -> Option<&Token> {
+        let i = *self.significant.get(self.pos + n)?;
+        Some(&self.tokens[i])
+    }
+
+    /// the kind of the next token that is not part of a doc comment
+    pub fn peek_after_docs(&self) -> Option<TokenKind> {
+        self.significant[self.pos..]
+            .iter()
+            .map(|i| self.tokens[*i].kind)
+            .find(|kind| *kind != TokenKind::DocComment)
+    }
+
+    /// the span of the next token, or an empty span at the end of the input
+    pub fn peek_span(&self) -> Span {
+        self.peek_token()
+            .map(|token| token.span)
+            .unwrap_or(Span::empty(self.offset + self.src.len()))
+    }
+
+    /// the span from the start of `start` to the end of the last consumed token
+    pub fn span_from(&self, start: Span) -> Span {
+        let end = match self.pos.checked_sub(1) {
+            Some(pos) => self.tokens[self.significant[pos]].span.end.max(start.start),
+            None => start.end,
+        };
+
+        Span::new(start.start, end)
+    }
+
+    pub fn bump(&mut self) -> Option<Token> {
+        let i = *self.significant.get(self.pos)?;
+        let token = self.tokens[i];
+
+        self.emit_trivia(i);
+        self.builder.token(token.kind.into(), self.text(&token));
+
+        self.emitted = i + 1;
+        self.pos += 1;
+        Some(token)
+    }
+
+    pub fn next_if(&mut self, kind: TokenKind) -> Option<Token> {
+        match self.peek() == Some(kind) {
+            true => self.bump(),
+            false => None,
+        }
+    }
+
+    pub fn expect(&mut self, kind: TokenKind) -> AstBuildResult<Token> {
+        self.next_if(kind).ok_or_else(|| {
+            self.error(AstBuildErrorKind::ExpectedToken {
+                expected: kind,
+                found: self.peek(),
+            })
+        })
+    }
+
+    /// adds the trivia before the token at `end` to the syntax tree
+    fn emit_trivia(&mut self, end: usize) {
+        for i in self.emitted..end {
+            let token = self.tokens[i];
+            self.builder.token(token.kind.into(), self.text(&token));
+        }
+
+        self.emitted = self.emitted.max(end);
+    }
+
+    /// marks the start of a node that may be finished once it has been parsed. trivia before the
+    /// next token is attached to the enclosing node
+    pub fn checkpoint(&mut self) -> Marker {
+        let next = self
+            .significant
+            .get(self.pos)
+            .copied()
+            .unwrap_or(self.tokens.len());
+
+        self.emit_trivia(next);
+        Marker {
+            checkpoint: self.builder.checkpoint(),
+            start: self.peek_span(),
+        }
+    }
+
+    /// marks the start of the input, unlike `checkpoint` the leading trivia is part of the node
+    pub fn root(&mut self) -> Marker {
+        Marker {
+            checkpoint: self.builder.checkpoint(),
+            start: Span::empty(self.offset),
+        }
+    }
+
+    /// wraps the whole input, including the trailing trivia, in a node. returns the span of the
+    /// input
+    pub fn finish_root(&mut self, marker: Marker, kind: NodeKind) -> Span {
+        self.emit_trivia(self.tokens.len());
+        self.builder.start_node_at(marker.checkpoint, kind.into());
+        self.builder.finish_node();
+        Span::new(marker.start.start, self.offset + self.src.len())
+    }
+
+    /// wraps everything added to the syntax tree since `marker` in a node, returns the span of
+    /// the node
+    pub fn finish_node(&mut self, marker: Marker, kind: NodeKind) -> Span {
+        self.builder.start_node_at(marker.checkpoint, kind.into());
+        self.builder.finish_node();
+        self.span_from(marker.start)
+    }
+
+    /// an error located at the next token
+    pub fn error(&self, kind: AstBuildErrorKind) -> AstBuildError {
+        AstBuildError {
+            kind,
+            span: self.peek_span(),
         }
     }
 }
@@ -0,0 +1,172 @@
+use crate::{
+    ast::{
+        self, walk_alias_decl_mut, walk_assign_stmt_mut, walk_block_mut, walk_bounds_mut,
+        walk_ctrl_stmt_mut, walk_expr_mut, walk_fn_decl_mut, walk_if_stmt_mut,
+        walk_iface_alias_item_mut, walk_iface_decl_mut, walk_iface_fn_item_mut,
+        walk_interpolated_str_mut, walk_struct_expr_mut, walk_struct_field_mut,
+        walk_while_stmt_mut, AstBuildResult, Block, Decl, Expr, SourceFile, VisitorMut,
+    },
+    parser::{Parse, Parser},
+    span::Span,
+    syntax::{NodeKind, SyntaxKind, SyntaxNode},
+};
+use rowan::{GreenNode, NodeOrToken, TextRange, TextSize};
+
+/// replaces the source text in `span` with `text`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn apply(&self, src: &mut String) {
+        src.replace_range(self.span.as_range(), &self.text);
+    }
+}
+
+fn is_decl(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::Node(NodeKind::AliasDecl | NodeKind::FnDecl | NodeKind::IfaceDecl)
+    )
+}
+
+impl Parse {
+    /// applies an edit by reparsing the smallest block or top level decl that strictly contains
+    /// it. every other subtree of the syntax tree is reused, and only the AST of the enclosing top
+    /// level decl is rebuilt. edits that can't be contained fall back to a full reparse, so the
+    /// result is always identical to parsing the edited source from scratch. on error the parse
+    /// is left unchanged
+    pub fn reparse(&mut self, edit: &TextEdit) -> AstBuildResult<()> {
+        let root = self.syntax();
+        let mut src = root.to_string();
+        edit.apply(&mut src);
+
+        if let Some((green, decl_idx, decl)) = Self::reparse_subtree(&root, edit) {
+            let delta = edit.text.len() as isize - edit.span.len() as isize;
+            let mut shift = ShiftSpans(delta);
+
+            for decl in &mut self.file.decls[decl_idx + 1..] {
+                shift.visit_decl_mut(decl);
+            }
+
+            self.green = green;
+            self.file.decls[decl_idx] = decl;
+            self.file.span = Span::new(0, src.len());
+            return Ok(());
+        }
+
+        let mut parse = Parser::parse(&src)?;
+        parse.file.path = self.file.path.clone();
+        *self = parse;
+        Ok(())
+    }
+
+    fn reparse_subtree(root: &SyntaxNode, edit: &TextEdit) -> Option<(GreenNode, usize, Decl)> {
+        let range = TextRange::new(
+            TextSize::try_from(edit.span.start).ok()?,
+            TextSize::try_from(edit.span.end).ok()?,
+        );
+
+        // the first and last token of the node must survive the edit, otherwise the edit could
+        // merge it with its neighbours
+        let node = root
+            .covering_element(range)
+            .ancestors()
+            .filter(|node| {
+                let node_range = node.text_range();
+                node_range.start() < range.start() && range.end() < node_range.end()
+            })
+            .find(|node| {
+                node.kind() == SyntaxKind::Node(NodeKind::Block)
+                    || is_decl(node.kind()) && node.parent().as_ref() == Some(root)
+            })?;
+
+        let offset = usize::from(node.text_range().start());
+        let mut text = node.to_string();
+        TextEdit {
+            span: Span::new(edit.span.start - offset, edit.span.end - offset),
+            text: edit.text.clone(),
+        }
+        .apply(&mut text);
+
+        let green = match node.kind() {
+            SyntaxKind::Node(NodeKind::Block) => Parser::parse_exact::<Block>(&text, offset)?.0,
+            _ => Parser::parse_exact::<Decl>(&text, offset)?.0,
+        };
+
+        let decl_node = node
+            .ancestors()
+            .find(|node| node.parent().as_ref() == Some(root))?;
+
+        let decl_idx = root
+            .children()
+            .filter(|node| is_decl(node.kind()))
+            .position(|node| node == decl_node)?;
+
+        let new_root = SyntaxNode::new_root(node.replace_with(green));
+        let new_decl = new_root
+            .children_with_tokens()
+            .nth(decl_node.index())
+            .and_then(NodeOrToken::into_node)?;
+
+        let decl_offset = new_decl.text_range().start().into();
+        let (_, decl) = Parser::parse_exact::<Decl>(&new_decl.to_string(), decl_offset)?;
+        SourceFile::check_decl(&decl).ok()?;
+        Some((new_root.green().into_owned(), decl_idx, decl))
+    }
+}
+
+/// moves every span in the visited nodes, used for the decls after an edit
+struct ShiftSpans(isize);
+
+impl ShiftSpans {
+    fn shift(&self, span: &mut Span) {
+        let shift = |pos: usize| pos.checked_add_signed(self.0).unwrap();
+        *span = Span::new(shift(span.start), shift(span.end));
+    }
+}
+
+macro_rules! shift_spans {
+    ($($visit:ident($ty:ty) $(=> $walk:ident)?;)*) => {$(
+        fn $visit(&mut self, node: &mut $ty) {
+            self.shift(&mut node.span);
+            $($walk(self, node);)?
+        }
+    )*};
+}
+
+impl VisitorMut for ShiftSpans {
+    shift_spans! {
+        visit_alias_decl_mut(ast::AliasDecl) => walk_alias_decl_mut;
+        visit_fn_decl_mut(ast::FnDecl) => walk_fn_decl_mut;
+        visit_iface_decl_mut(ast::IfaceDecl) => walk_iface_decl_mut;
+        visit_iface_alias_item_mut(ast::IfaceAliasItem) => walk_iface_alias_item_mut;
+        visit_iface_fn_item_mut(ast::IfaceFnItem) => walk_iface_fn_item_mut;
+        visit_doc_comment_mut(ast::DocComment);
+        visit_bounds_mut(ast::Bounds) => walk_bounds_mut;
+        visit_alias_mut(ast::Alias);
+        visit_block_mut(ast::Block) => walk_block_mut;
+        visit_struct_expr_mut(ast::StructExpr) => walk_struct_expr_mut;
+        visit_struct_field_mut(ast::StructField) => walk_struct_field_mut;
+        visit_bool_lit_mut(ast::BoolLit);
+        visit_int_lit_mut(ast::IntLit);
+        visit_float_lit_mut(ast::FloatLit);
+        visit_chr_lit_mut(ast::ChrLit);
+        visit_str_lit_mut(ast::StrLit);
+        visit_interpolated_str_mut(ast::InterpolatedStr) => walk_interpolated_str_mut;
+        visit_assign_stmt_mut(ast::AssignStmt) => walk_assign_stmt_mut;
+        visit_ctrl_stmt_mut(ast::CtrlStmt) => walk_ctrl_stmt_mut;
+        visit_if_stmt_mut(ast::IfStmt) => walk_if_stmt_mut;
+        visit_while_stmt_mut(ast::WhileStmt) => walk_while_stmt_mut;
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::UnaryExpr { span, .. } | Expr::BinaryExpr { span, .. } = expr {
+            self.shift(span);
+        }
+
+        walk_expr_mut(self, expr);
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{
+    ast::{
+        Alias, AliasDecl, AliasEval, AstBuildErrorKind, AstBuildResult, BinOp, Block, Bounds, Decl,
+        Expr, FnDecl, SourceFile, Stmt, StructExpr,
+    },
+    diagnostic::Diagnostic,
+    eval::{Evaluator, Flow, Frame, Value},
+    lexer::{Lexer, TokenKind},
+    parser::Parser,
+    sema::{self, Analysis, DefId},
+    span::Span,
+};
+use std::{any::type_name, cell::RefCell, collections::HashMap, rc::Rc};
+
+/// whether `src` needs more lines before it can be parsed, because a brace, paren, string or
+/// block comment is left open
+pub fn is_incomplete(src: &str) -> bool {
+    let (tokens, errors) = Lexer::tokenize(src);
+    let depth = tokens.iter().fold(0isize, |depth, token| match token.kind {
+        TokenKind::LBrace | TokenKind::LParen | TokenKind::InterpExprStart => depth + 1,
+        TokenKind::RBrace | TokenKind::RParen | TokenKind::InterpExprEnd => depth - 1,
+        _ => depth,
+    });
+
+    depth > 0
+        || errors.iter().any(|err| {
+            matches!(
+                err.kind,
+                AstBuildErrorKind::UnterminatedStr {}
+                    | AstBuildErrorKind::UnterminatedBlockComment {}
+            )
+        })
+}
+
+/// one piece of REPL input, statements and expressions are separated by ';'
+#[derive(Debug, Clone)]
+pub enum Input {
+    Stmt(Stmt),
+    Expr(Expr),
+}
+
+/// parses a line of REPL input as a sequence of decls, statements and expressions. unlike in a
+/// block, the last item doesn't need a ';'
+pub fn parse_input(src: &str) -> AstBuildResult<Vec<Input>> {
+    parse_input_at(src, 0)
+}
+
+/// parses REPL input that starts at `offset` in the session's source
+pub fn parse_input_at(src: &str, offset: usize) -> AstBuildResult<Vec<Input>> {
+    let mut parser = Parser::new_at(src, offset)?;
+    let mut inputs = Vec::new();
+
+    while parser.peek().is_some() {
+        let input = match Stmt::parse_or_expr(&mut parser)? {
+            Some(Ok(stmt)) => Input::Stmt(stmt),
+            Some(Err(expr)) => Input::Expr(expr),
+            None => {
+                return Err(parser.error(AstBuildErrorKind::ExpectedNode {
+                    expected_node: type_name::<Stmt>(),
+                    found: parser.peek(),
+                }))
+            }
+        };
+
+        inputs.push(input);
+        if parser.next_if(TokenKind::Semi).is_none() && parser.peek().is_some() {
+            parser.expect(TokenKind::Semi)?;
+        }
+    }
+
+    Ok(inputs)
+}
+
+/// the nodes the REPL adds around the input have spans past the end of any source, so they can't
+/// collide with the spans of the input
+const HIDDEN_SPAN: usize = usize::MAX / 2;
+
+fn hidden_span(i: usize) -> Span {
+    Span::empty(HIDDEN_SPAN + i)
+}
+
+fn hidden_alias(name: &str, i: usize) -> Alias {
+    Alias {
+        alias: name.to_string(),
+        span: hidden_span(i),
+    }
+}
+
+fn unit_expr(i: usize) -> Expr {
+    Expr::Struct(StructExpr {
+        default_vis: None,
+        fields: Vec::new(),
+        span: hidden_span(i),
+    })
+}
+
+/// the statements and bindings of earlier inputs. a session behaves like the body of one fn:
+/// every input is checked together with the statements before it, then only its own statements
+/// are evaluated. like in a fn, a name can't be declared twice
+#[derive(Debug, Default)]
+pub struct Session {
+    /// every input so far, the spans of diagnostics point into it
+    src: String,
+    stmts: Vec<Stmt>,
+    /// the values of `let` and `var` bindings, by the span of their name
+    slots: HashMap<Span, Rc<RefCell<Value>>>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// every input evaluated so far, diagnostics are rendered against it
+    pub fn src(&self) -> &str {
+        &self.src
+    }
+
+    /// runs a line of input, returns a line of output for every decl and expression. evaluation
+    /// stops at the first error, the statements evaluated before it are kept
+    pub fn eval(&mut self, src: &str) -> Vec<Result<String, Diagnostic>> {
+        let offset = self.src.len();
+        self.src.push_str(src);
+        if !src.ends_with('\n') {
+            self.src.push('\n');
+        }
+
+        let input_span = Span::new(offset, offset + src.trim_end().len());
+        match self.eval_at(src, offset) {
+            Ok(out) => out,
+            Err(errors) => errors
+                .into_iter()
+                .map(|err| Err(self.in_source(err, input_span)))
+                .collect(),
+        }
+    }
+
+    fn eval_at(
+        &mut self,
+        src: &str,
+        offset: usize,
+    ) -> Result<Vec<Result<String, Diagnostic>>, Vec<Diagnostic>> {
+        let inputs = parse_input_at(src, offset).map_err(|err| vec![err.into()])?;
+
+        // expressions are bound to hidden names, so their types are checked like any binding
+        let stmts: Vec<_> = inputs
+            .into_iter()
+            .map(|input| match input {
+                Input::Stmt(stmt) => (stmt, false),
+                Input::Expr(expr) => {
+                    let decl = AliasDecl {
+                        doc: None,
+                        eval: AliasEval::Let,
+                        alias: Alias {
+                            alias: "<expr>".to_string(),
+                            span: Span::empty(expr.span().start),
+                        },
+                        bounds: None,
+                        span: expr.span(),
+                        rhs: expr,
+                    };
+
+                    (Stmt::Decl(Decl::Alias(decl)), true)
+                }
+            })
+            .collect();
+
+        let file = self.program(stmts.iter().map(|(stmt, _)| stmt.clone()));
+        let analysis = sema::analyze(&file);
+        if !analysis.errors.is_empty() {
+            return Err(analysis.errors);
+        }
+
+        let mut evaluator =
+            Evaluator::new(&file, &analysis.res).with_consts(analysis.consts.values.clone());
+
+        let mut frame = Frame::new();
+        for (span, slot) in &self.slots {
+            if let Some(id) = analysis.res.decls.get(span) {
+                frame.insert(*id, slot.clone());
+            }
+        }
+
+        evaluator.push_frame(frame);
+
+        let Decl::Fn(body) = &file.decls[0] else {
+            unreachable!()
+        };
+
+        let new_stmts = &body.body.stmts[self.stmts.len()..];
+        let mut out = Vec::new();
+        let mut evaluated = 0;
+
+        for (stmt, (_, is_expr)) in new_stmts.iter().zip(&stmts) {
+            if let Err(flow) = evaluator.stmt(stmt) {
+                let err = match flow {
+                    Flow::Error(err) => err,
+                    Flow::Return(_) => {
+                        Diagnostic::error("`return` can't be used outside of a fn", stmt.span())
+                    }
+                    Flow::Break(span) | Flow::Continue(span) => {
+                        Diagnostic::error("`break` and `continue` can only be used in a loop", span)
+                    }
+                    Flow::Failed => Diagnostic::error("evaluation failed", stmt.span()),
+                };
+
+                out.push(Err(err));
+                break;
+            }
+
+            let frame = evaluator.pop_frame().unwrap_or_default();
+            out.extend(describe(stmt, *is_expr, &frame, &analysis).map(Ok));
+            evaluator.push_frame(frame);
+            evaluated += 1;
+        }
+
+        let frame = evaluator.pop_frame().unwrap_or_default();
+        for (stmt, is_expr) in stmts.into_iter().take(evaluated) {
+            if let Stmt::Decl(Decl::Alias(decl)) = &stmt {
+                let slot = analysis
+                    .res
+                    .decls
+                    .get(&decl.alias.span)
+                    .and_then(|id| frame.get(id));
+
+                if let (Some(slot), false) = (slot, is_expr) {
+                    self.slots.insert(decl.alias.span, slot.clone());
+                }
+            }
+
+            if !is_expr {
+                self.stmts.push(stmt);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// the earlier statements and `stmts` as the body of a fn that takes no argument
+    fn program(&self, stmts: impl IntoIterator<Item = Stmt>) -> SourceFile {
+        // `()::{<arg>}` binds the argument to a name that can't be written
+        let param = Expr::BinaryExpr {
+            lhs: Rc::new(unit_expr(0)),
+            rhs: Rc::new(Expr::Block(Block {
+                stmts: Vec::new(),
+                expr: Some(Rc::new(Expr::Alias(hidden_alias("<arg>", 1)))),
+                span: hidden_span(2),
+            })),
+            op: BinOp::EvalPath,
+            span: hidden_span(3),
+        };
+
+        let signature = Expr::BinaryExpr {
+            lhs: Rc::new(param),
+            rhs: Rc::new(unit_expr(4)),
+            op: BinOp::FnArrow,
+            span: hidden_span(5),
+        };
+
+        let body = FnDecl {
+            doc: None,
+            alias: hidden_alias("<repl>", 6),
+            bounds: Some(Bounds {
+                expr: signature,
+                span: hidden_span(7),
+            }),
+            body: Block {
+                stmts: self.stmts.iter().cloned().chain(stmts).collect(),
+                expr: None,
+                span: hidden_span(8),
+            },
+            span: hidden_span(9),
+            owner: None,
+        };
+
+        SourceFile {
+            path: None,
+            decls: vec![Decl::Fn(body)],
+            span: Span::new(0, self.src.len()),
+        }
+    }
+
+    /// keeps a diagnostic inside the session's source, spans of the nodes added around the input
+    /// are replaced with the span of the input
+    fn in_source(&self, mut err: Diagnostic, input: Span) -> Diagnostic {
+        if err.span.end > self.src.len() {
+            err.span = input;
+        }
+
+        err.labels.retain(|label| label.span.end <= self.src.len());
+        err
+    }
+}
+
+/// the line printed for a statement: the value and type of bindings and expressions, and the
+/// name of other decls
+fn describe(stmt: &Stmt, is_expr: bool, frame: &Frame, analysis: &Analysis) -> Option<String> {
+    let Stmt::Decl(decl) = stmt else {
+        return None;
+    };
+
+    let (alias, kind) = match decl {
+        Decl::Alias(decl) => (&decl.alias, decl.eval.clone()),
+        Decl::Fn(decl) => return Some(format!("fn {}", decl.alias.alias)),
+        Decl::Iface(decl) => return Some(format!("iface {}", decl.alias.alias)),
+    };
+
+    let id = analysis.res.decls.get(&alias.span);
+    let val = match kind {
+        AliasEval::Type => return Some(format!("type {}", alias.alias)),
+        AliasEval::Const => id.and_then(|id| analysis.consts.values.get(id)).cloned(),
+        AliasEval::Let | AliasEval::Var => id
+            .and_then(|id| frame.get(id))
+            .map(|slot| slot.borrow().clone()),
+    }?;
+
+    let ty = ty_of(analysis, id);
+    match is_expr {
+        true => Some(format!("{val}: {ty}")),
+        false => Some(format!("{}: {ty} = {val}", alias.alias)),
+    }
+}
+
+fn ty_of(analysis: &Analysis, id: Option<&DefId>) -> String {
+    id.and_then(|id| analysis.types.values.get(id))
+        .map(ToString::to_string)
+        .unwrap_or_else(|| "_".to_string())
+}
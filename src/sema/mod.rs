@@ -0,0 +1,55 @@
+//! semantic passes that run on a parsed file
+
+mod mutability;
+mod resolve;
+mod typeck;
+mod types;
+
+pub use mutability::*;
+pub use resolve::*;
+pub use typeck::*;
+pub use types::*;
+
+use crate::{
+    ast::SourceFile,
+    diagnostic::Diagnostic,
+    eval::{eval_consts, Consts},
+};
+
+/// the results of every semantic pass over a file
+#[derive(Debug)]
+pub struct Analysis {
+    pub res: Resolution,
+    pub types: Types,
+    /// empty when the file has other errors
+    pub consts: Consts,
+    /// sorted by position
+    pub errors: Vec<Diagnostic>,
+}
+
+/// runs every semantic pass over `file`
+pub fn analyze(file: &SourceFile) -> Analysis {
+    let (res, mut errors) = resolve(file);
+    errors.extend(check_mutability(file, &res));
+
+    let (types, type_errors) = check_types(file, &res);
+    errors.extend(type_errors);
+
+    let mut consts = Consts::default();
+    if errors.is_empty() {
+        (consts, errors) = eval_consts(file, &res);
+    }
+
+    errors.sort_by_key(|err| err.span.start);
+    Analysis {
+        res,
+        types,
+        consts,
+        errors,
+    }
+}
+
+/// runs every semantic pass over `file`, the diagnostics are sorted by position
+pub fn check(file: &SourceFile) -> Vec<Diagnostic> {
+    analyze(file).errors
+}
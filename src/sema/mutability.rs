@@ -0,0 +1,102 @@
+use crate::{
+    ast::{
+        walk_assign_stmt, walk_fn_decl, walk_iface_fn_item, Alias, AssignStmt, BinOp, Expr, FnDecl,
+        IfaceFnItem, PostOp, SourceFile, Visitor,
+    },
+    diagnostic::Diagnostic,
+    sema::{DefKind, Resolution},
+};
+
+/// rejects assignments to bindings that can't be mutated, and to fields reached through them.
+/// only `var` bindings and the named return fields of a fn can be assigned, and the fields of
+/// `self` inside a method
+pub fn check_mutability(file: &SourceFile, res: &Resolution) -> Vec<Diagnostic> {
+    let mut checker = MutChecker {
+        res,
+        methods: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    checker.visit_source_file(file);
+    checker.errors
+}
+
+struct MutChecker<'a> {
+    res: &'a Resolution,
+    /// whether each fn being checked is a method, the innermost is last
+    methods: Vec<bool>,
+    errors: Vec<Diagnostic>,
+}
+
+/// the binding that an assignment to `expr` modifies, and whether it is reached through a
+/// field. assignments through a dereference modify the pointee, not the binding
+fn assigned_root(expr: &Expr) -> Option<(&Alias, bool)> {
+    match expr {
+        Expr::Alias(alias) => Some((alias, false)),
+        Expr::BinaryExpr {
+            lhs,
+            op: BinOp::ExecPath | BinOp::EvalPath,
+            ..
+        } => assigned_root(lhs).map(|(alias, _)| (alias, true)),
+        Expr::UnaryExpr {
+            expr,
+            op: PostOp::Unwrap,
+            ..
+        } => assigned_root(expr),
+        _ => None,
+    }
+}
+
+impl Visitor<'_> for MutChecker<'_> {
+    fn visit_fn_decl(&mut self, decl: &FnDecl) {
+        self.methods.push(decl.owner.is_some());
+        walk_fn_decl(self, decl);
+        self.methods.pop();
+    }
+
+    fn visit_iface_fn_item(&mut self, item: &IfaceFnItem) {
+        self.methods.push(false);
+        walk_iface_fn_item(self, item);
+        self.methods.pop();
+    }
+
+    fn visit_assign_stmt(&mut self, stmt: &AssignStmt) {
+        walk_assign_stmt(self, stmt);
+
+        let Some((alias, is_field)) = assigned_root(&stmt.lhs) else {
+            return;
+        };
+
+        let Some(def) = self.res.lookup(alias.span) else {
+            return;
+        };
+
+        let name = &def.name;
+        let (reason, decl) = match def.kind {
+            DefKind::Var | DefKind::Ret => return,
+            DefKind::SelfArg if is_field && self.methods.last() == Some(&true) => return,
+            DefKind::Let => (
+                "it is declared with `let`, use `var` to make it mutable",
+                format!("`{name}` is declared here"),
+            ),
+            DefKind::Const => ("it is a constant", format!("`{name}` is declared here")),
+            DefKind::Arg | DefKind::SelfArg => {
+                ("it is an argument", format!("`{name}` is bound here"))
+            }
+            DefKind::Prim | DefKind::Type | DefKind::Fn | DefKind::Iface | DefKind::SelfType => {
+                ("it isn't a value", format!("`{name}` is declared here"))
+            }
+        };
+
+        let target = match is_field {
+            true => format!("a field of `{name}`"),
+            false => format!("`{name}`"),
+        };
+
+        let err = Diagnostic::error(format!("cannot assign to {target}, {reason}"), stmt.span);
+        self.errors.push(match def.span {
+            Some(span) => err.with_label(span, decl),
+            None => err,
+        });
+    }
+}
@@ -0,0 +1,529 @@
+use crate::{
+    ast::{
+        walk_block, walk_expr, Alias, AliasDecl, AliasEval, BinOp, Block, Bounds, Decl, Expr,
+        FnDecl, IfaceDecl, IfaceItem, SourceFile, Stmt, StructField, Visitor,
+    },
+    diagnostic::Diagnostic,
+    sema::Prim,
+    span::Span,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DefId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefKind {
+    /// a primitive type such as `i32`
+    Prim,
+    Const,
+    Type,
+    Fn,
+    Iface,
+    Let,
+    Var,
+    /// the argument of a fn, `arg` unless it was destructured
+    Arg,
+    /// a named field of a fn's return value, it has to be assigned before the fn returns
+    Ret,
+    /// the curried receiver of a method, `self` unless it was destructured
+    SelfArg,
+    /// the type that implements an interface
+    SelfType,
+}
+
+#[derive(Debug, Clone)]
+pub struct Def {
+    pub name: String,
+    pub kind: DefKind,
+    /// the alias that declares the name. implicit names point at the signature that binds them,
+    /// primitives have no span
+    pub span: Option<Span>,
+}
+
+/// links every alias in a file to the name it refers to. `decls` and `uses` are keyed by the
+/// span of the alias
+#[derive(Debug, Default)]
+pub struct Resolution {
+    pub defs: Vec<Def>,
+    pub decls: HashMap<Span, DefId>,
+    pub uses: HashMap<Span, DefId>,
+    /// the methods declared with `fn T::name`, keyed by the def of `T` and the name
+    pub methods: HashMap<(DefId, String), DefId>,
+}
+
+impl Resolution {
+    pub fn def(&self, id: DefId) -> &Def {
+        &self.defs[id.0]
+    }
+
+    /// the def an alias refers to, or the def it declares
+    pub fn lookup(&self, span: Span) -> Option<&Def> {
+        let id = self.uses.get(&span).or_else(|| self.decls.get(&span))?;
+        Some(self.def(*id))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScopeKind {
+    Prelude,
+    File,
+    Iface,
+    /// the names bound by a fn's signature
+    Fn,
+    Block,
+}
+
+#[derive(Debug)]
+struct Scope {
+    kind: ScopeKind,
+    names: HashMap<String, DefId>,
+}
+
+/// resolves every alias in `file`. a name can't be declared twice in the same scope, and locals
+/// can't shadow other names declared in the same fn. shadowing names from outside the fn, like
+/// constants or primitive types, is allowed
+pub fn resolve(file: &SourceFile) -> (Resolution, Vec<Diagnostic>) {
+    let mut resolver = Resolver::default();
+    resolver.push(ScopeKind::Prelude);
+
+    for prim in Prim::ALL {
+        resolver.declare(prim.name(), DefKind::Prim, None);
+    }
+
+    resolver.visit_source_file(file);
+    (resolver.res, resolver.errors)
+}
+
+#[derive(Default)]
+struct Resolver {
+    res: Resolution,
+    scopes: Vec<Scope>,
+    errors: Vec<Diagnostic>,
+}
+
+impl Resolver {
+    fn push(&mut self, kind: ScopeKind) {
+        self.scopes.push(Scope {
+            kind,
+            names: HashMap::new(),
+        });
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn get(&self, name: &str) -> Option<DefId> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.names.get(name).copied())
+    }
+
+    /// the def that a new local would illegally shadow, a name declared in an enclosing scope of
+    /// the same fn
+    fn shadowed(&self, name: &str) -> Option<DefId> {
+        let mut scopes = self.scopes.iter().rev();
+        if scopes.next()?.kind != ScopeKind::Block {
+            return None;
+        }
+
+        for scope in scopes {
+            if !matches!(scope.kind, ScopeKind::Block | ScopeKind::Fn) {
+                break;
+            }
+
+            if let Some(id) = scope.names.get(name) {
+                return Some(*id);
+            }
+
+            if scope.kind == ScopeKind::Fn {
+                break;
+            }
+        }
+
+        None
+    }
+
+    fn declare(&mut self, name: &str, kind: DefKind, span: Option<Span>) -> DefId {
+        let id = DefId(self.res.defs.len());
+        self.res.defs.push(Def {
+            name: name.to_string(),
+            kind,
+            span,
+        });
+
+        if let Some(span) = span {
+            self.res.decls.insert(span, id);
+
+            let scope = self.scopes.last().unwrap();
+            if let Some(prev) = scope.names.get(name) {
+                let err =
+                    Diagnostic::error(format!("`{name}` is already declared in this scope"), span);
+                self.errors.push(label_prev(err, self.res.def(*prev)));
+            } else if let Some(prev) = self.shadowed(name) {
+                let err = Diagnostic::error(format!("`{name}` shadows an existing name"), span);
+                self.errors.push(label_prev(err, self.res.def(prev)));
+            }
+        }
+
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .names
+            .insert(name.to_string(), id);
+        id
+    }
+
+    /// declares the decls that are visible before they appear: constants, types, fns and
+    /// interfaces. `let` and `var` are only visible after their declaration
+    fn hoist<'a>(&mut self, decls: impl IntoIterator<Item = &'a Decl>) {
+        let mut methods = Vec::new();
+        for decl in decls {
+            let (alias, kind) = match decl {
+                Decl::Alias(decl) => match decl.eval {
+                    AliasEval::Const => (&decl.alias, DefKind::Const),
+                    AliasEval::Type => (&decl.alias, DefKind::Type),
+                    AliasEval::Let | AliasEval::Var => continue,
+                },
+                Decl::Fn(decl) if decl.owner.is_some() => {
+                    methods.push(decl);
+                    continue;
+                }
+                Decl::Fn(decl) => (&decl.alias, DefKind::Fn),
+                Decl::Iface(decl) => (&decl.alias, DefKind::Iface),
+            };
+
+            self.declare(&alias.alias, kind, Some(alias.span));
+        }
+
+        // methods can be declared before the type they belong to
+        for decl in methods {
+            self.method(decl);
+        }
+    }
+
+    /// declares a method of the type named by `fn T::name`. methods aren't in scope, they are
+    /// reached through the type with `T::name`
+    fn method(&mut self, decl: &FnDecl) {
+        let (Some(owner), alias) = (&decl.owner, &decl.alias) else {
+            return;
+        };
+
+        let id = DefId(self.res.defs.len());
+        self.res.defs.push(Def {
+            name: alias.alias.clone(),
+            kind: DefKind::Fn,
+            span: Some(alias.span),
+        });
+        self.res.decls.insert(alias.span, id);
+
+        self.visit_alias(owner);
+        let Some(ty) = self.res.uses.get(&owner.span).copied() else {
+            return;
+        };
+
+        if self.res.def(ty).kind != DefKind::Type {
+            let err = Diagnostic::error(
+                format!(
+                    "cannot declare methods on `{}`, it isn't declared with `type`",
+                    owner.alias
+                ),
+                owner.span,
+            );
+            self.errors.push(label_prev(err, self.res.def(ty)));
+            return;
+        }
+
+        let key = (ty, alias.alias.clone());
+        if let Some(prev) = self.res.methods.get(&key) {
+            let err = Diagnostic::error(
+                format!("`{}` already has a method `{}`", owner.alias, alias.alias),
+                alias.span,
+            );
+            self.errors.push(label_prev(err, self.res.def(*prev)));
+            return;
+        }
+
+        self.res.methods.insert(key, id);
+    }
+
+    /// the type a method is declared on
+    fn owner(&self, decl: &FnDecl) -> Option<DefId> {
+        let id = *self.res.uses.get(&decl.owner.as_ref()?.span)?;
+        (self.res.def(id).kind == DefKind::Type).then_some(id)
+    }
+
+    /// resolves `T::name` to a method of the type `T`
+    fn method_use(&mut self, ty: &Alias, name: &Alias) {
+        let Some(id) = self.res.uses.get(&ty.span).copied() else {
+            return;
+        };
+
+        if self.res.def(id).kind != DefKind::Type {
+            return;
+        }
+
+        match self.res.methods.get(&(id, name.alias.clone())) {
+            Some(method) => {
+                self.res.uses.insert(name.span, *method);
+            }
+            None => self.errors.push(Diagnostic::error(
+                format!("`{}` has no method `{}`", self.res.def(id).name, name.alias),
+                name.span,
+            )),
+        }
+    }
+
+    /// binds the names introduced by a fn's signature `recv -> arg -> ret`, where the receiver is
+    /// optional. fns without a signature still take `arg`
+    fn signature(&mut self, bounds: Option<&Bounds>) {
+        let Some(bounds) = bounds else {
+            self.declare("arg", DefKind::Arg, None);
+            return;
+        };
+
+        let mut parts = Vec::new();
+        let mut expr = &bounds.expr;
+        while let Expr::BinaryExpr {
+            lhs,
+            rhs,
+            op: BinOp::FnArrow,
+            ..
+        } = expr
+        {
+            parts.push(&**lhs);
+            expr = rhs;
+        }
+
+        let params = match parts.len() {
+            0 => {
+                self.declare("arg", DefKind::Arg, Some(bounds.span));
+                self.visit_expr(expr);
+                return;
+            }
+            1 => vec![("arg", DefKind::Arg, parts[0])],
+            _ => vec![
+                ("self", DefKind::SelfArg, parts[0]),
+                ("arg", DefKind::Arg, parts[1]),
+            ],
+        };
+
+        // every arrow after the argument is part of the return type
+        for part in parts.iter().skip(params.len()) {
+            self.visit_expr(part);
+        }
+
+        for (name, kind, param) in params {
+            self.param(name, kind, param);
+        }
+
+        match expr {
+            Expr::Struct(ret) => {
+                for field in &ret.fields {
+                    self.field_types(field);
+                    self.declare(&field.alias.alias, DefKind::Ret, Some(field.alias.span));
+                }
+            }
+            ret => self.visit_expr(ret),
+        }
+    }
+
+    /// binds a parameter. `T::{name}` renames it, `T::(a, b)` destructures it and the fields of
+    /// an inline struct are bound alongside it
+    fn param(&mut self, name: &str, kind: DefKind, param: &Expr) {
+        match param {
+            Expr::BinaryExpr {
+                lhs,
+                rhs,
+                op: BinOp::EvalPath,
+                ..
+            } if matches!(&**rhs, Expr::Block(_) | Expr::Struct(_)) => {
+                self.visit_expr(lhs);
+                match &**rhs {
+                    Expr::Block(Block {
+                        stmts,
+                        expr: Some(tail),
+                        ..
+                    }) if stmts.is_empty() => match &**tail {
+                        Expr::Alias(alias) => {
+                            self.declare(&alias.alias, kind, Some(alias.span));
+                        }
+                        tail => self.visit_expr(tail),
+                    },
+                    Expr::Struct(fields) => {
+                        for field in &fields.fields {
+                            self.field_types(field);
+                            self.declare(&field.alias.alias, DefKind::Arg, Some(field.alias.span));
+                        }
+                    }
+                    rhs => self.visit_expr(rhs),
+                }
+            }
+            Expr::Struct(fields) => {
+                self.declare(name, kind, Some(param.span()));
+                for field in &fields.fields {
+                    self.field_types(field);
+                    self.declare(&field.alias.alias, DefKind::Arg, Some(field.alias.span));
+                }
+            }
+            ty => {
+                self.visit_expr(ty);
+                self.declare(name, kind, Some(ty.span()));
+            }
+        }
+    }
+
+    /// visits a field's type and default value, but not its name
+    fn field_types(&mut self, field: &StructField) {
+        if let Some(bounds) = &field.bounds {
+            self.visit_expr(&bounds.expr);
+        }
+
+        if let Some(val) = &field.val {
+            self.visit_expr(val);
+        }
+    }
+
+    /// checks a fn's signature and body. in a method, `Self` is the type it is declared on
+    fn fn_body(&mut self, bounds: Option<&Bounds>, body: &Block, self_ty: Option<DefId>) {
+        self.push(ScopeKind::Fn);
+        if let Some(ty) = self_ty {
+            let scope = self.scopes.last_mut().unwrap();
+            scope.names.insert("Self".to_string(), ty);
+        }
+
+        self.signature(bounds);
+        self.visit_block(body);
+        self.pop();
+    }
+}
+
+fn label_prev(err: Diagnostic, prev: &Def) -> Diagnostic {
+    match prev.span {
+        Some(span) => err.with_label(span, format!("`{}` is declared here", prev.name)),
+        None => err,
+    }
+}
+
+impl Visitor<'_> for Resolver {
+    fn visit_source_file(&mut self, file: &SourceFile) {
+        self.push(ScopeKind::File);
+        self.hoist(&file.decls);
+
+        for decl in &file.decls {
+            self.visit_decl(decl);
+        }
+
+        self.pop();
+    }
+
+    fn visit_alias_decl(&mut self, decl: &AliasDecl) {
+        if let Some(bounds) = &decl.bounds {
+            self.visit_expr(&bounds.expr);
+        }
+
+        self.visit_expr(&decl.rhs);
+
+        let kind = match decl.eval {
+            AliasEval::Let => DefKind::Let,
+            AliasEval::Var => DefKind::Var,
+            AliasEval::Const | AliasEval::Type => return,
+        };
+
+        self.declare(&decl.alias.alias, kind, Some(decl.alias.span));
+    }
+
+    fn visit_fn_decl(&mut self, decl: &FnDecl) {
+        let self_ty = self.owner(decl);
+        self.fn_body(decl.bounds.as_ref(), &decl.body, self_ty);
+    }
+
+    fn visit_iface_decl(&mut self, decl: &IfaceDecl) {
+        self.push(ScopeKind::Iface);
+        self.declare("Self", DefKind::SelfType, None);
+
+        for item in &decl.items {
+            let (alias, kind) = match item {
+                IfaceItem::Alias(item) if matches!(item.eval, AliasEval::Type) => {
+                    (&item.alias, DefKind::Type)
+                }
+                IfaceItem::Alias(item) => (&item.alias, DefKind::Const),
+                IfaceItem::Fn(item) => (&item.alias, DefKind::Fn),
+            };
+
+            self.declare(&alias.alias, kind, Some(alias.span));
+        }
+
+        for item in &decl.items {
+            match item {
+                IfaceItem::Alias(item) => {
+                    if let Some(bounds) = &item.bounds {
+                        self.visit_expr(&bounds.expr);
+                    }
+
+                    if let Some(default) = &item.default {
+                        self.visit_expr(default);
+                    }
+                }
+                IfaceItem::Fn(item) => match &item.default {
+                    Some(body) => self.fn_body(item.bounds.as_ref(), body, None),
+                    None => {
+                        if let Some(bounds) = &item.bounds {
+                            self.visit_expr(&bounds.expr);
+                        }
+                    }
+                },
+            }
+        }
+
+        self.pop();
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.push(ScopeKind::Block);
+        self.hoist(block.stmts.iter().filter_map(|stmt| match stmt {
+            Stmt::Decl(decl) => Some(decl),
+            _ => None,
+        }));
+
+        walk_block(self, block);
+        self.pop();
+    }
+
+    fn visit_struct_field(&mut self, field: &StructField) {
+        self.field_types(field);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            // the rhs of a path is a member of the lhs, not a name in scope
+            Expr::BinaryExpr {
+                lhs,
+                rhs,
+                op: op @ (BinOp::EvalPath | BinOp::ExecPath),
+                ..
+            } if matches!(&**rhs, Expr::Alias(_)) => {
+                self.visit_expr(lhs);
+                if let (BinOp::EvalPath, Expr::Alias(ty), Expr::Alias(name)) = (op, &**lhs, &**rhs)
+                {
+                    self.method_use(ty, name);
+                }
+            }
+            expr => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_alias(&mut self, alias: &Alias) {
+        match self.get(&alias.alias) {
+            Some(id) => {
+                self.res.uses.insert(alias.span, id);
+            }
+            None => self.errors.push(Diagnostic::error(
+                format!("cannot find `{}` in this scope", alias.alias),
+                alias.span,
+            )),
+        }
+    }
+}
@@ -0,0 +1,1299 @@
+use crate::{
+    ast::{
+        walk_assign_stmt, Alias, AliasDecl, AliasEval, AssignStmt, BinOp, Block, Bounds, CtrlOp,
+        Decl, Expr, FieldVis, FnDecl, IfStmt, IfaceAliasItem, IfaceDecl, IfaceItem, IntLit,
+        InterpolatedStrPart, PostOp, SourceFile, Stmt, StructExpr, StructField, Visitor,
+    },
+    diagnostic::Diagnostic,
+    sema::{DefId, DefKind, FieldTy, NamedTy, Prim, Resolution, Ty},
+    span::Span,
+};
+use std::collections::{HashMap, HashSet};
+
+/// the types given to the value defs of a file
+#[derive(Debug, Default)]
+pub struct Types {
+    pub values: HashMap<DefId, Ty>,
+}
+
+/// checks the types of a resolved file: annotated bindings, fn returns and the branches of `if`.
+/// numbers without an expected type default to `i32` and `f32`
+pub fn check_types(file: &SourceFile, res: &Resolution) -> (Types, Vec<Diagnostic>) {
+    let mut checker = TypeChecker {
+        res,
+        items: HashMap::new(),
+        assigns: HashMap::new(),
+        inferred: HashSet::new(),
+        values: HashMap::new(),
+        types: HashMap::new(),
+        pending: HashSet::new(),
+        done: HashSet::new(),
+        fns: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    checker.register(&file.decls);
+    for decl in &file.decls {
+        checker.decl(decl);
+    }
+
+    let types = Types {
+        values: checker.values,
+    };
+
+    (types, checker.errors)
+}
+
+/// a decl that can be used before it is checked
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Alias(&'a AliasDecl),
+    Fn(&'a FnDecl),
+    IfaceAlias(&'a IfaceAliasItem),
+}
+
+/// where an expected type comes from, it is labelled in mismatch errors
+#[derive(Clone, Copy)]
+struct Expected<'t> {
+    ty: &'t Ty,
+    origin: Option<Span>,
+}
+
+/// the fn whose body is being checked
+struct FnScope {
+    ret: Ty,
+    /// where the return type is written
+    ret_span: Option<Span>,
+    /// the type a method is declared on, its private and read-only fields can be written
+    receiver: Option<DefId>,
+}
+
+struct TypeChecker<'a> {
+    res: &'a Resolution,
+    items: HashMap<DefId, Item<'a>>,
+    /// the assignments to every `var` in the fns being checked
+    assigns: HashMap<DefId, Vec<&'a AssignStmt>>,
+    /// the vars whose type is a union inferred from their assignments
+    inferred: HashSet<DefId>,
+    /// the type of every value def that has been checked
+    values: HashMap<DefId, Ty>,
+    /// the type that every type def stands for
+    types: HashMap<DefId, Ty>,
+    /// the defs whose type is being computed, used to break cycles
+    pending: HashSet<DefId>,
+    done: HashSet<DefId>,
+    fns: Vec<FnScope>,
+    errors: Vec<Diagnostic>,
+}
+
+impl<'a> TypeChecker<'a> {
+    fn decl_id(&self, alias: &Alias) -> Option<DefId> {
+        self.res.decls.get(&alias.span).copied()
+    }
+
+    fn use_id(&self, alias: &Alias) -> Option<DefId> {
+        self.res.uses.get(&alias.span).copied()
+    }
+
+    /// makes the decls of a scope available before they are checked, mirroring the hoisting
+    /// done by the resolver
+    fn register(&mut self, decls: impl IntoIterator<Item = &'a Decl>) {
+        for decl in decls {
+            match decl {
+                Decl::Alias(decl) => {
+                    if let Some(id) = self.decl_id(&decl.alias) {
+                        self.items.insert(id, Item::Alias(decl));
+                    }
+                }
+                Decl::Fn(decl) => {
+                    if let Some(id) = self.decl_id(&decl.alias) {
+                        self.items.insert(id, Item::Fn(decl));
+                    }
+                }
+                Decl::Iface(decl) => {
+                    for item in &decl.items {
+                        if let IfaceItem::Alias(item) = item {
+                            if let Some(id) = self.decl_id(&item.alias) {
+                                self.items.insert(id, Item::IfaceAlias(item));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn mismatch(&mut self, found: &Ty, expected: Expected, span: Span) {
+        if found.compatible(expected.ty) {
+            return;
+        }
+
+        let err = Diagnostic::error(format!("expected `{}`, found `{found}`", expected.ty), span);
+
+        self.errors.push(match expected.origin {
+            Some(origin) => err.with_label(origin, "expected because of this"),
+            None => err,
+        });
+    }
+
+    /// checks `expr` against an expected type and reports a mismatch
+    fn check(&mut self, expr: &'a Expr, expected: Expected) -> Ty {
+        let found = self.expr(expr, Some(expected.ty));
+        self.mismatch(&found, expected, expr.span());
+        found
+    }
+
+    fn decl(&mut self, decl: &'a Decl) {
+        match decl {
+            Decl::Alias(decl) => self.alias_decl(decl),
+            Decl::Fn(decl) => self.fn_decl(decl),
+            Decl::Iface(decl) => {
+                for item in &decl.items {
+                    match item {
+                        IfaceItem::Alias(item) => {
+                            if let Some(id) = self.decl_id(&item.alias) {
+                                self.iface_alias(id, item);
+                            }
+                        }
+                        IfaceItem::Fn(item) => {
+                            if let Some(body) = &item.default {
+                                self.fn_body(item.bounds.as_ref(), body, None);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn alias_decl(&mut self, decl: &'a AliasDecl) {
+        let Some(id) = self.decl_id(&decl.alias) else {
+            return;
+        };
+
+        if !self.done.insert(id) {
+            return;
+        }
+
+        self.pending.insert(id);
+        if matches!(decl.eval, AliasEval::Type) {
+            let repr = match &decl.rhs {
+                Expr::Struct(rhs) => self.struct_ty(rhs, true),
+                rhs => self.ty(rhs),
+            };
+            let ty = Ty::Named(NamedTy {
+                def: id,
+                name: decl.alias.alias.clone(),
+                repr: repr.into(),
+            });
+
+            self.types.insert(id, ty);
+        } else {
+            let ty = match &decl.bounds {
+                Some(bounds) => {
+                    let ty = self.ty(&bounds.expr);
+                    let expected = Expected {
+                        ty: &ty,
+                        origin: Some(bounds.expr.span()),
+                    };
+
+                    self.check(&decl.rhs, expected);
+                    ty
+                }
+                None => match self.infer_union(id, decl) {
+                    Some(ty) => ty,
+                    None => self.expr(&decl.rhs, None),
+                },
+            };
+
+            self.values.insert(id, ty);
+        }
+
+        self.pending.remove(&id);
+    }
+
+    /// the type of a `var` that is given structs with one field, when the field isn't always
+    /// the same. the type is the union of every field that is assigned in the fn
+    fn infer_union(&mut self, id: DefId, decl: &'a AliasDecl) -> Option<Ty> {
+        let (AliasEval::Var, Expr::Struct(rhs)) = (&decl.eval, &decl.rhs) else {
+            return None;
+        };
+
+        let [first] = rhs.fields.as_slice() else {
+            return None;
+        };
+
+        let variants: Vec<_> = self
+            .assigns
+            .get(&id)?
+            .iter()
+            .filter_map(|stmt| match &stmt.rhs {
+                Expr::Struct(StructExpr { fields, .. }) if fields.len() == 1 => Some(&fields[0]),
+                _ => None,
+            })
+            .collect();
+
+        if variants
+            .iter()
+            .all(|field| field.alias.alias == first.alias.alias)
+        {
+            return None;
+        }
+
+        self.unnamed_vis(rhs);
+        let mut union = vec![FieldTy {
+            name: first.alias.alias.clone(),
+            ty: self.field_ty(first, None),
+            vis: Some(FieldVis::Pub),
+            has_default: false,
+        }];
+
+        // the values are checked again with their assignments, so errors are only kept there
+        let errors = self.errors.len();
+        for field in variants {
+            if union
+                .iter()
+                .all(|variant| variant.name != field.alias.alias)
+            {
+                union.push(FieldTy {
+                    name: field.alias.alias.clone(),
+                    ty: self.field_ty(field, None),
+                    vis: Some(FieldVis::Pub),
+                    has_default: false,
+                });
+            }
+        }
+
+        self.errors.truncate(errors);
+        self.inferred.insert(id);
+        Some(Ty::Union(union))
+    }
+
+    fn iface_alias(&mut self, id: DefId, item: &'a IfaceAliasItem) {
+        if !self.done.insert(id) {
+            return;
+        }
+
+        self.pending.insert(id);
+        let ty = match &item.bounds {
+            Some(bounds) => self.ty(&bounds.expr),
+            None => Ty::Unknown,
+        };
+
+        if let Some(default) = &item.default {
+            match item.eval {
+                AliasEval::Type => {
+                    let default = self.ty(default);
+                    self.types.insert(id, default);
+                }
+                _ => {
+                    let expected = Expected {
+                        ty: &ty,
+                        origin: item.bounds.as_ref().map(|bounds| bounds.expr.span()),
+                    };
+
+                    self.check(default, expected);
+                }
+            }
+        }
+
+        self.values.insert(id, ty);
+        self.pending.remove(&id);
+    }
+
+    fn fn_decl(&mut self, decl: &'a FnDecl) {
+        if let Some(id) = self.decl_id(&decl.alias) {
+            self.done.insert(id);
+        }
+
+        let receiver = decl.owner.as_ref().and_then(|owner| self.use_id(owner));
+        self.fn_body(decl.bounds.as_ref(), &decl.body, receiver);
+    }
+
+    /// splits a signature into its parameters and return type, the same way the resolver binds
+    /// it. a signature without an arrow is only a return type
+    fn split_signature(bounds: &'a Bounds) -> (Vec<&'a Expr>, &'a Expr) {
+        let mut parts = Vec::new();
+        let mut expr = &bounds.expr;
+        while let Expr::BinaryExpr {
+            lhs,
+            rhs,
+            op: BinOp::FnArrow,
+            ..
+        } = expr
+        {
+            parts.push(&**lhs);
+            expr = rhs;
+        }
+
+        (parts, expr)
+    }
+
+    /// the type of a fn with the given signature, without binding its parameters
+    fn fn_ty(&mut self, bounds: Option<&'a Bounds>) -> Ty {
+        match bounds {
+            Some(bounds) => match &bounds.expr {
+                expr @ Expr::BinaryExpr {
+                    op: BinOp::FnArrow, ..
+                } => self.ty(expr),
+                ret => Ty::Fn(Ty::unit().into(), self.ty(ret).into()),
+            },
+            None => Ty::Fn(Ty::unit().into(), Ty::unit().into()),
+        }
+    }
+
+    /// binds the parameters of a signature and returns the return type, and whether the return
+    /// value is made of named fields that are assigned in the body
+    fn signature(&mut self, bounds: Option<&'a Bounds>) -> (Ty, bool) {
+        let Some(bounds) = bounds else {
+            return (Ty::unit(), false);
+        };
+
+        let (parts, ret) = Self::split_signature(bounds);
+        let params = parts.len().min(2);
+
+        // a signature without arrows is only a return type, the fn still takes `()` as `arg`
+        if let (true, Some(id)) = (parts.is_empty(), self.res.decls.get(&bounds.span)) {
+            self.values.insert(*id, Ty::unit());
+        }
+
+        for param in &parts[..params] {
+            self.param(param);
+        }
+
+        let mut ret_ty = self.ty(ret);
+        for part in parts[params..].iter().rev() {
+            ret_ty = Ty::Fn(self.ty(part).into(), ret_ty.into());
+        }
+
+        let named = match ret {
+            Expr::Struct(ret) if !ret.fields.is_empty() => {
+                for field in &ret.fields {
+                    if let (Some(id), Some(field_ty)) =
+                        (self.decl_id(&field.alias), ret_ty.field(&field.alias.alias))
+                    {
+                        self.values.insert(id, field_ty.ty.clone());
+                    }
+                }
+
+                true
+            }
+            _ => false,
+        };
+
+        (ret_ty, named)
+    }
+
+    /// gives the names bound by a parameter their types
+    fn param(&mut self, param: &'a Expr) {
+        match param {
+            Expr::BinaryExpr {
+                lhs,
+                rhs,
+                op: BinOp::EvalPath,
+                ..
+            } if matches!(&**rhs, Expr::Block(_) | Expr::Struct(_)) => {
+                let ty = self.ty(lhs);
+                match &**rhs {
+                    Expr::Block(Block {
+                        stmts,
+                        expr: Some(tail),
+                        ..
+                    }) if stmts.is_empty() => {
+                        if let Expr::Alias(alias) = &**tail {
+                            if let Some(id) = self.decl_id(alias) {
+                                self.values.insert(id, ty.clone());
+                            }
+                        }
+                    }
+                    Expr::Struct(fields) => {
+                        for field in &fields.fields {
+                            let field_ty = ty
+                                .field(&field.alias.alias)
+                                .map_or(Ty::Unknown, |field| field.ty.clone());
+
+                            if let Some(id) = self.decl_id(&field.alias) {
+                                self.values.insert(id, field_ty);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            param => {
+                let ty = self.ty(param);
+                if let Expr::Struct(fields) = param {
+                    for field in &fields.fields {
+                        if let (Some(id), Some(field_ty)) =
+                            (self.decl_id(&field.alias), ty.field(&field.alias.alias))
+                        {
+                            self.values.insert(id, field_ty.ty.clone());
+                        }
+                    }
+                }
+
+                if let Some(id) = self.res.decls.get(&param.span()) {
+                    self.values.insert(*id, ty);
+                }
+            }
+        }
+    }
+
+    fn fn_body(&mut self, bounds: Option<&'a Bounds>, body: &'a Block, receiver: Option<DefId>) {
+        let (ret, named) = self.signature(bounds);
+        let origin = bounds.map(|bounds| Self::split_signature(bounds).1.span());
+
+        self.fns.push(FnScope {
+            ret: ret.clone(),
+            ret_span: origin,
+            receiver,
+        });
+
+        let expected = Expected { ty: &ret, origin };
+
+        let mut collector = AssignCollector::default();
+        collector.visit_block(body);
+        for stmt in collector.assigns {
+            let Expr::Alias(alias) = &stmt.lhs else {
+                continue;
+            };
+
+            if let Some(id) = self.use_id(alias) {
+                if self.res.def(id).kind == DefKind::Var {
+                    self.assigns.entry(id).or_default().push(stmt);
+                }
+            }
+        }
+
+        let found = self.block(body, Some(expected.ty));
+        match &body.expr {
+            Some(tail) => self.mismatch(&found, expected, tail.span()),
+            // named return fields are returned implicitly once they are assigned
+            None if named => {}
+            None => self.mismatch(&found, expected, Span::empty(body.span.end - 1)),
+        }
+
+        self.fns.pop();
+    }
+
+    fn block(&mut self, block: &'a Block, expected: Option<&Ty>) -> Ty {
+        self.register(block.stmts.iter().filter_map(|stmt| match stmt {
+            Stmt::Decl(decl) => Some(decl),
+            _ => None,
+        }));
+
+        for stmt in &block.stmts {
+            self.stmt(stmt);
+        }
+
+        match &block.expr {
+            Some(tail) => self.expr(tail, expected),
+            None if block.stmts.last().is_some_and(diverges) => Ty::Unknown,
+            None => Ty::unit(),
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Decl(decl) => self.decl(decl),
+            Stmt::Assign(stmt) => {
+                if let (Expr::Alias(alias), Expr::Struct(rhs)) = (&stmt.lhs, &stmt.rhs) {
+                    let id = self.use_id(alias);
+                    if rhs.fields.len() != 1 && id.is_some_and(|id| self.inferred.contains(&id)) {
+                        let def = self.res.def(id.unwrap());
+                        let err = Diagnostic::error(
+                            format!("type of `{}` cannot be inferred", alias.alias),
+                            stmt.rhs.span(),
+                        );
+
+                        self.errors.push(match def.span {
+                            Some(span) => err.with_label(
+                                span,
+                                "inferred as a union, every value must have exactly one field",
+                            ),
+                            None => err,
+                        });
+
+                        self.struct_expr(rhs, None);
+                        return;
+                    }
+                }
+
+                let ty = self.place(&stmt.lhs);
+                let expected = Expected {
+                    ty: &ty,
+                    origin: Some(stmt.lhs.span()),
+                };
+
+                self.check(&stmt.rhs, expected);
+            }
+            Stmt::Ctrl(stmt) => match (&stmt.op, &stmt.rhs) {
+                (CtrlOp::Return, rhs) => {
+                    let (ret, origin) = match self.fns.last() {
+                        Some(scope) => (scope.ret.clone(), scope.ret_span),
+                        None => (Ty::Unknown, None),
+                    };
+
+                    let expected = Expected { ty: &ret, origin };
+
+                    match rhs {
+                        Some(rhs) => {
+                            self.check(rhs, expected);
+                        }
+                        None => self.mismatch(&Ty::unit(), expected, stmt.span),
+                    }
+                }
+                (_, Some(rhs)) => {
+                    self.expr(rhs, None);
+                }
+                (_, None) => {}
+            },
+            Stmt::If(stmt) => {
+                self.if_expr(stmt, None, false);
+            }
+            Stmt::While(stmt) => {
+                self.cond(&stmt.lhs);
+                self.expr(&stmt.rhs, None);
+            }
+        }
+    }
+
+    fn cond(&mut self, cond: &'a Expr) {
+        let bool = Ty::Prim(Prim::Bool);
+        self.check(
+            cond,
+            Expected {
+                ty: &bool,
+                origin: None,
+            },
+        );
+    }
+
+    /// the type of an `if`. when its value is used every branch must have the same type, and an
+    /// `if` without `else` has no value
+    fn if_expr(&mut self, stmt: &'a IfStmt, expected: Option<&Ty>, is_value: bool) -> Ty {
+        self.cond(&stmt.lhs);
+        let then = self.expr(&stmt.rhs, expected);
+
+        let Some(chain) = &stmt.chain else {
+            return Ty::unit();
+        };
+
+        let other = self.expr(chain, expected.or(Some(&then)));
+        if !is_value {
+            return Ty::unit();
+        }
+
+        if !other.compatible(&then) {
+            let err = Diagnostic::error(
+                format!("`if` and `else` have different types, expected `{then}`, found `{other}`"),
+                chain.span(),
+            );
+
+            self.errors
+                .push(err.with_label(stmt.rhs.span(), format!("this branch is `{then}`")));
+            return Ty::Unknown;
+        }
+
+        match then {
+            Ty::Unknown => other,
+            then => then,
+        }
+    }
+
+    /// the type of a value expression. `expected` only guides literals, the caller reports
+    /// mismatches
+    fn expr(&mut self, expr: &'a Expr, expected: Option<&Ty>) -> Ty {
+        match expr {
+            Expr::Alias(alias) => self.alias_ty(alias),
+            Expr::Block(block) => self.block(block, expected),
+            Expr::Struct(expr) => self.struct_expr(expr, expected),
+
+            Expr::Bool(_) => Ty::Prim(Prim::Bool),
+            Expr::Int(lit) => {
+                let ty = match expected {
+                    Some(Ty::Prim(prim)) if prim.is_int() || prim.is_float() => *prim,
+                    _ => Prim::I32,
+                };
+
+                let val = int_val(lit);
+                if let Some((min, max)) = ty.int_range() {
+                    if val < min || val > max {
+                        self.errors.push(Diagnostic::error(
+                            format!("`{val}` doesn't fit in `{}`", ty.name()),
+                            lit.span,
+                        ));
+                    }
+                }
+
+                Ty::Prim(ty)
+            }
+            Expr::Float(_) => match expected {
+                Some(Ty::Prim(prim)) if prim.is_float() => Ty::Prim(*prim),
+                _ => Ty::Prim(Prim::F32),
+            },
+            Expr::Chr(_) => Ty::Prim(Prim::Char),
+            Expr::Str(_) => Ty::Prim(Prim::Str),
+            Expr::InterpolatedStr(lit) => {
+                for part in &lit.parts {
+                    if let InterpolatedStrPart::Expr(expr) = part {
+                        self.expr(expr, None);
+                    }
+                }
+
+                Ty::Prim(Prim::Str)
+            }
+
+            Expr::IfStmt(stmt) => self.if_expr(stmt, expected, true),
+            Expr::WhileStmt(stmt) => {
+                self.cond(&stmt.lhs);
+                self.expr(&stmt.rhs, None);
+                Ty::unit()
+            }
+
+            Expr::UnaryExpr { expr, op, span } => {
+                let inner = match (op, expected) {
+                    (PostOp::Ref, Some(Ty::Ref(inner))) => self.expr(expr, Some(inner)),
+                    _ => self.expr(expr, None),
+                };
+
+                match (op, inner) {
+                    (PostOp::Ref, inner) => Ty::Ref(inner.into()),
+                    (PostOp::Deref, Ty::Ref(inner)) => *inner,
+                    (PostOp::Deref, Ty::Unknown) => Ty::Unknown,
+                    (PostOp::Deref, inner) => {
+                        self.errors.push(Diagnostic::error(
+                            format!("`{inner}` isn't a reference, it can't be dereferenced"),
+                            *span,
+                        ));
+
+                        Ty::Unknown
+                    }
+                    (PostOp::Unwrap, _) => Ty::Unknown,
+                }
+            }
+            Expr::BinaryExpr { lhs, rhs, op, .. } => match (op, &**rhs) {
+                (BinOp::ExecPath, Expr::Alias(field)) => {
+                    let lhs = self.expr(lhs, None);
+                    self.access(&lhs, field, false)
+                }
+                (BinOp::EvalPath, Expr::Block(block)) => self.cast(lhs, block),
+                (BinOp::EvalPath, Expr::Alias(method)) => self.alias_ty(method),
+                (BinOp::PipeR, _) => self.call(rhs, lhs),
+                (BinOp::PipeL, _) => self.call(lhs, rhs),
+                (BinOp::EvalPath, Expr::Struct(fields)) => {
+                    let ty = self.ty(lhs);
+                    self.construct(lhs, ty, fields)
+                }
+                _ => {
+                    self.expr(lhs, None);
+                    self.expr(rhs, None);
+                    Ty::Unknown
+                }
+            },
+        }
+    }
+
+    /// the type of a call made with `arg > callee` or `callee < arg`
+    fn call(&mut self, callee: &'a Expr, arg: &'a Expr) -> Ty {
+        match self.expr(callee, None) {
+            Ty::Fn(param, ret) => {
+                let expected = Expected {
+                    ty: &param,
+                    origin: None,
+                };
+
+                self.check(arg, expected);
+                *ret
+            }
+            Ty::Unknown => {
+                self.expr(arg, None);
+                Ty::Unknown
+            }
+            ty => {
+                self.expr(arg, None);
+                self.errors.push(Diagnostic::error(
+                    format!("`{ty}` isn't a fn, it can't be called"),
+                    callee.span(),
+                ));
+
+                Ty::Unknown
+            }
+        }
+    }
+
+    /// the type of `ty::{value}`. a value can be cast to a type it is compatible with, a named
+    /// type can be cast to and from its representation, and structs with the same fields can be
+    /// cast to one another unless the target has private fields
+    fn cast(&mut self, ty: &'a Expr, value: &'a Block) -> Ty {
+        let target = self.ty(ty);
+        let found = self.block(value, Some(target.repr()));
+        let found = match &value.expr {
+            Some(expr) if value.stmts.is_empty() => self.adopt_literals(found, expr, &target),
+            _ => found,
+        };
+
+        if !found.castable(&target) {
+            let err = Diagnostic::error(
+                format!("cannot cast a value of type `{found}` to `{target}`"),
+                value.span,
+            );
+
+            self.errors.push(match &target {
+                Ty::Named(named) => err.with_label(
+                    ty.span(),
+                    format!("`{target}` is represented as `{}`", named.repr),
+                ),
+                _ => err,
+            });
+        } else if !found.compatible(&target) {
+            let restriction = target
+                .fields()
+                .and_then(|fields| self.strictest(&target, fields.iter()));
+
+            if let Some(kind) = restriction {
+                let err = Diagnostic::error(
+                    format!("cannot cast to type with {kind} fields"),
+                    value.span,
+                );
+                self.errors
+                    .push(err.with_label(ty.span(), format!("`{target}` has {kind} fields")));
+            }
+        }
+
+        target
+    }
+
+    /// `found` with the fields of the struct literal `value` that are untyped numbers given the
+    /// type of the same field of `target`, so `(lat = 0)` can be cast to `(lat: f64)` even after
+    /// it has been bound with `let`
+    fn adopt_literals(&self, found: Ty, value: &'a Expr, target: &Ty) -> Ty {
+        let (Ty::Struct(fields), Some(targets), Some(lit)) =
+            (&found, target.fields(), self.struct_literal(value))
+        else {
+            return found;
+        };
+
+        let fields = fields
+            .iter()
+            .map(|field| {
+                let val = lit
+                    .fields
+                    .iter()
+                    .find(|lit| lit.alias.alias == field.name && lit.bounds.is_none())
+                    .and_then(|lit| lit.val.as_ref());
+                let target = targets.iter().find(|target| target.name == field.name);
+
+                let ty = match (val, target) {
+                    (Some(val), Some(target)) if fits(val, &target.ty) => target.ty.clone(),
+                    (Some(val), Some(target)) => {
+                        self.adopt_literals(field.ty.clone(), val, &target.ty)
+                    }
+                    _ => field.ty.clone(),
+                };
+
+                FieldTy {
+                    ty,
+                    ..field.clone()
+                }
+            })
+            .collect();
+
+        Ty::Struct(fields)
+    }
+
+    /// the struct literal `expr` is, or that the `let` or `const` binding it names was given
+    /// without an annotation
+    fn struct_literal(&self, expr: &'a Expr) -> Option<&'a StructExpr> {
+        match expr {
+            Expr::Struct(lit) => Some(lit),
+            Expr::Alias(alias) => match self.items.get(&self.use_id(alias)?)? {
+                Item::Alias(AliasDecl {
+                    eval: AliasEval::Let | AliasEval::Const,
+                    bounds: None,
+                    rhs: Expr::Struct(lit),
+                    ..
+                }) => Some(lit),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn struct_expr(&mut self, expr: &'a StructExpr, expected: Option<&Ty>) -> Ty {
+        self.unnamed_vis(expr);
+        if let (Some(union @ Ty::Union(variants)), [field]) =
+            (expected.map(Ty::repr), expr.fields.as_slice())
+        {
+            if let Some(variant) = variants.iter().find(|v| v.name == field.alias.alias) {
+                let found = self.field_ty(field, Some(&variant.ty));
+                let expected = Expected {
+                    ty: &variant.ty,
+                    origin: None,
+                };
+
+                self.mismatch(&found, expected, field.span);
+                return union.clone();
+            }
+        }
+
+        let fields = expr
+            .fields
+            .iter()
+            .map(|field| {
+                let expected = expected.and_then(|ty| ty.field(&field.alias.alias));
+                let ty = self.field_ty(field, expected.map(|field| &field.ty));
+
+                FieldTy {
+                    name: field.alias.alias.clone(),
+                    ty,
+                    vis: Some(FieldVis::Pub),
+                    has_default: true,
+                }
+            })
+            .collect();
+
+        Ty::Struct(fields)
+    }
+
+    /// the type of a struct type expression. only the fields of a struct declared with `type`,
+    /// and of the structs declared inline in it, can be private
+    fn struct_ty(&mut self, expr: &'a StructExpr, is_named: bool) -> Ty {
+        if !is_named {
+            self.unnamed_vis(expr);
+        }
+
+        let fields = expr
+            .fields
+            .iter()
+            .map(|field| {
+                let mut ty = self.field_ty(field, None);
+
+                // the fields of a struct declared inline in a named type belong to that type
+                if is_named {
+                    if let Some(Bounds {
+                        expr: Expr::Struct(_),
+                        ..
+                    }) = &field.bounds
+                    {
+                        make_private(&mut ty);
+                    }
+                }
+
+                // a struct field can be left out when all of its own fields have defaults
+                let has_default = field.val.is_some()
+                    || ty
+                        .fields()
+                        .is_some_and(|fields| fields.iter().all(|field| field.has_default));
+
+                FieldTy {
+                    name: field.alias.alias.clone(),
+                    ty,
+                    vis: match is_named {
+                        true => field.vis.or(expr.default_vis),
+                        false => Some(FieldVis::Pub),
+                    },
+                    has_default,
+                }
+            })
+            .collect();
+
+        Ty::Struct(fields)
+    }
+
+    /// rejects visibility modifiers in a struct that isn't declared with `type`
+    fn unnamed_vis(&mut self, expr: &StructExpr) {
+        let msg = "fields of un-named structs are always public";
+        if expr.default_vis.is_some() {
+            self.errors.push(Diagnostic::error(msg, expr.span));
+        }
+
+        for field in &expr.fields {
+            if field.vis.is_some() {
+                self.errors.push(Diagnostic::error(msg, field.span));
+            }
+        }
+    }
+
+    /// the type of `ty::(fields)`. a type can't be constructed when it has private fields
+    /// without defaults, and private fields can't be given a value. a struct literal given to a
+    /// struct field constructs the type of the field, so its name can be left out
+    fn construct(&mut self, ty: &'a Expr, target: Ty, expr: &'a StructExpr) -> Ty {
+        self.unnamed_vis(expr);
+        let Some(fields) = target.fields() else {
+            if !matches!(target, Ty::Unknown) {
+                self.errors.push(Diagnostic::error(
+                    format!("`{target}` isn't a struct, it can't be constructed with fields"),
+                    ty.span(),
+                ));
+            }
+
+            for field in &expr.fields {
+                self.field_ty(field, None);
+            }
+
+            return target;
+        };
+
+        let mut restricted = Vec::new();
+        for field in &expr.fields {
+            let name = &field.alias.alias;
+            let Some(field_ty) = fields.iter().find(|field_ty| &field_ty.name == name) else {
+                self.errors.push(Diagnostic::error(
+                    format!("`{target}` has no field `{name}`"),
+                    field.alias.span,
+                ));
+
+                self.field_ty(field, None);
+                continue;
+            };
+
+            if let Some(kind) = self.restriction(&target, field_ty) {
+                restricted.push((field, kind));
+            }
+
+            match (&field.bounds, &field.val) {
+                (None, Some(Expr::Struct(val))) if field_ty.ty.fields().is_some() => {
+                    self.construct(ty, field_ty.ty.clone(), val);
+                }
+                (None, Some(val)) => {
+                    let expected = Expected {
+                        ty: &field_ty.ty,
+                        origin: None,
+                    };
+
+                    self.check(val, expected);
+                }
+                _ => {
+                    let found = self.field_ty(field, Some(&field_ty.ty));
+                    let expected = Expected {
+                        ty: &field_ty.ty,
+                        origin: None,
+                    };
+
+                    self.mismatch(&found, expected, field.span);
+                }
+            }
+        }
+
+        let required = self.strictest(&target, fields.iter().filter(|field| !field.has_default));
+
+        if let Some(kind) = required {
+            let err = Diagnostic::error(
+                format!("cannot construct a type with {kind} fields"),
+                expr.span,
+            );
+
+            self.errors.push(err.with_label(
+                ty.span(),
+                format!("`{target}` has {kind} fields without defaults"),
+            ));
+
+            return target;
+        }
+
+        for kind in [PRIVATE, READ_ONLY] {
+            let fields: Vec<_> = restricted
+                .iter()
+                .filter(|(_, restriction)| *restriction == kind)
+                .map(|(field, _)| field)
+                .collect();
+
+            if let Some(first) = fields.first() {
+                let names = fields.iter().map(|field| field.alias.alias.as_str());
+                let msg = match fields.len() {
+                    1 => format!("{} is a {kind} field", list(names)),
+                    _ => format!("{} are {kind} fields", list(names)),
+                };
+
+                self.errors.push(Diagnostic::error(msg, first.span));
+            }
+        }
+
+        let missing: Vec<_> = fields
+            .iter()
+            .filter(|field| !field.has_default)
+            .filter(|field| !expr.fields.iter().any(|f| f.alias.alias == field.name))
+            .map(|field| field.name.as_str())
+            .collect();
+
+        if !missing.is_empty() {
+            let msg = match missing.len() {
+                1 => format!("missing field {} in `{target}`", list(missing)),
+                _ => format!("missing fields {} in `{target}`", list(missing)),
+            };
+
+            self.errors.push(Diagnostic::error(msg, expr.span));
+        }
+
+        target
+    }
+
+    /// why a field of `owner` can't be written here, if it can't. private and read-only fields
+    /// can only be written by the methods of the type that declares them
+    fn restriction(&self, owner: &Ty, field: &FieldTy) -> Option<&'static str> {
+        let receiver = self.fns.last().and_then(|scope| scope.receiver);
+        let is_receiver = match owner {
+            Ty::Named(named) => receiver == Some(named.def),
+            inline => receiver
+                .and_then(|id| self.types.get(&id))
+                .is_some_and(|ty| declares(ty.repr(), inline)),
+        };
+
+        if is_receiver {
+            return None;
+        }
+
+        match field.vis {
+            None => Some(PRIVATE),
+            Some(FieldVis::PubGet) => Some(READ_ONLY),
+            Some(FieldVis::Pub) => None,
+        }
+    }
+
+    /// the strictest restriction on any of `fields`
+    fn strictest<'f>(
+        &self,
+        owner: &Ty,
+        fields: impl Iterator<Item = &'f FieldTy>,
+    ) -> Option<&'static str> {
+        fields
+            .filter_map(|field| self.restriction(owner, field))
+            .min_by_key(|kind| *kind != PRIVATE)
+    }
+
+    /// the type of `owner.field`. read-only fields can be read anywhere, private fields only in
+    /// methods. reading something that isn't a field may name a method, so it isn't an error
+    fn access(&mut self, owner: &Ty, field: &Alias, is_write: bool) -> Ty {
+        let Some(field_ty) = owner.field(&field.alias) else {
+            if is_write && !matches!(owner.repr(), Ty::Unknown) {
+                self.errors.push(Diagnostic::error(
+                    format!("`{owner}` has no field `{}`", field.alias),
+                    field.span,
+                ));
+            }
+
+            return Ty::Unknown;
+        };
+
+        match self.restriction(owner, field_ty) {
+            Some(PRIVATE) => self.errors.push(Diagnostic::error(
+                format!("`{}` is a private field of `{owner}`", field.alias),
+                field.span,
+            )),
+            Some(kind) if is_write => self.errors.push(Diagnostic::error(
+                format!(
+                    "cannot assign to `{}`, it is a {kind} field of `{owner}`",
+                    field.alias
+                ),
+                field.span,
+            )),
+            _ => {}
+        }
+
+        field_ty.ty.clone()
+    }
+
+    /// the type of the lhs of an assignment. every field on the way is written
+    fn place(&mut self, expr: &'a Expr) -> Ty {
+        match expr {
+            Expr::BinaryExpr {
+                lhs,
+                rhs,
+                op: BinOp::ExecPath,
+                ..
+            } => match &**rhs {
+                Expr::Alias(field) => {
+                    let owner = self.place(lhs);
+                    self.access(&owner, field, true)
+                }
+                _ => self.expr(expr, None),
+            },
+            expr => self.expr(expr, None),
+        }
+    }
+
+    /// the type of a struct field, from its annotation or its value
+    fn field_ty(&mut self, field: &'a StructField, expected: Option<&Ty>) -> Ty {
+        match (&field.bounds, &field.val) {
+            (Some(bounds), val) => {
+                let ty = self.ty(&bounds.expr);
+                if let Some(val) = val {
+                    let expected = Expected {
+                        ty: &ty,
+                        origin: Some(bounds.expr.span()),
+                    };
+
+                    self.check(val, expected);
+                }
+
+                ty
+            }
+            (None, Some(val)) => self.expr(val, expected),
+            (None, None) => Ty::Unknown,
+        }
+    }
+
+    /// the type of a value referenced by name
+    fn alias_ty(&mut self, alias: &Alias) -> Ty {
+        let Some(id) = self.use_id(alias) else {
+            return Ty::Unknown;
+        };
+
+        if let Some(ty) = self.values.get(&id) {
+            return ty.clone();
+        }
+
+        let def = self.res.def(id);
+        match (def.kind, self.items.get(&id).copied()) {
+            // fns without a signature take `()`
+            (DefKind::Arg, _) if def.span.is_none() => Ty::unit(),
+            _ if self.pending.contains(&id) => Ty::Unknown,
+            (DefKind::Const, Some(Item::Alias(decl))) => {
+                self.alias_decl(decl);
+                self.values.get(&id).cloned().unwrap_or(Ty::Unknown)
+            }
+            (DefKind::Const, Some(Item::IfaceAlias(item))) => {
+                self.iface_alias(id, item);
+                self.values.get(&id).cloned().unwrap_or(Ty::Unknown)
+            }
+            (DefKind::Fn, Some(Item::Fn(decl))) => {
+                self.pending.insert(id);
+                let ty = self.fn_ty(decl.bounds.as_ref());
+                self.pending.remove(&id);
+                self.values.insert(id, ty.clone());
+                ty
+            }
+            _ => Ty::Unknown,
+        }
+    }
+
+    /// the type described by a type expression
+    fn ty(&mut self, expr: &'a Expr) -> Ty {
+        match expr {
+            Expr::Alias(alias) => self.named_ty(alias),
+            Expr::Struct(expr) => self.struct_ty(expr, false),
+            Expr::UnaryExpr {
+                expr,
+                op: PostOp::Ref,
+                ..
+            } => Ty::Ref(self.ty(expr).into()),
+            Expr::BinaryExpr {
+                lhs,
+                rhs,
+                op: BinOp::FnArrow,
+                ..
+            } => Ty::Fn(self.ty(lhs).into(), self.ty(rhs).into()),
+            _ => Ty::Unknown,
+        }
+    }
+
+    fn named_ty(&mut self, alias: &Alias) -> Ty {
+        let Some(id) = self.use_id(alias) else {
+            return Ty::Unknown;
+        };
+
+        if let Some(ty) = self.types.get(&id) {
+            return ty.clone();
+        }
+
+        let def = self.res.def(id);
+        match (def.kind, self.items.get(&id).copied()) {
+            (DefKind::Prim, _) => Prim::from_name(&def.name).map_or(Ty::Unknown, Ty::Prim),
+            _ if self.pending.contains(&id) => Ty::Unknown,
+            (DefKind::Type, Some(Item::Alias(decl))) => {
+                self.alias_decl(decl);
+                self.types.get(&id).cloned().unwrap_or(Ty::Unknown)
+            }
+            (DefKind::Type | DefKind::SelfType | DefKind::Iface, _) => Ty::Unknown,
+            _ => {
+                self.errors.push(Diagnostic::error(
+                    format!("`{}` isn't a type", alias.alias),
+                    alias.span,
+                ));
+
+                Ty::Unknown
+            }
+        }
+    }
+}
+
+const PRIVATE: &str = "private";
+const READ_ONLY: &str = "read-only";
+
+/// joins names as "`a`, `b` and `c`"
+fn list<'n>(names: impl IntoIterator<Item = &'n str>) -> String {
+    let names: Vec<_> = names.into_iter().map(|name| format!("`{name}`")).collect();
+    match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {last}", rest.join(", ")),
+        None => String::new(),
+    }
+}
+
+/// the assignments in a fn body, without those in nested fns
+#[derive(Default)]
+struct AssignCollector<'a> {
+    assigns: Vec<&'a AssignStmt>,
+}
+
+impl<'a> Visitor<'a> for AssignCollector<'a> {
+    fn visit_assign_stmt(&mut self, stmt: &'a AssignStmt) {
+        self.assigns.push(stmt);
+        walk_assign_stmt(self, stmt);
+    }
+
+    fn visit_fn_decl(&mut self, _decl: &'a FnDecl) {}
+
+    fn visit_iface_decl(&mut self, _decl: &'a IfaceDecl) {}
+}
+
+/// whether control never reaches the end of a statement
+fn diverges(stmt: &Stmt) -> bool {
+    matches!(
+        stmt,
+        Stmt::Ctrl(ctrl) if matches!(ctrl.op, CtrlOp::Return | CtrlOp::Break | CtrlOp::Continue)
+    )
+}
+
+fn int_val(lit: &IntLit) -> i128 {
+    match lit.neg {
+        true => -(lit.val as i128),
+        false => lit.val as i128,
+    }
+}
+
+/// whether an untyped number can be given the type `ty`, integers can also be floats
+fn fits(val: &Expr, ty: &Ty) -> bool {
+    match (val, ty) {
+        (Expr::Int(lit), Ty::Prim(prim)) => {
+            prim.is_float()
+                || prim
+                    .int_range()
+                    .is_some_and(|(min, max)| (min..=max).contains(&int_val(lit)))
+        }
+        (Expr::Float(_), Ty::Prim(prim)) => prim.is_float(),
+        _ => false,
+    }
+}
+
+/// hides the fields of a struct declared inline in a named type, and of the structs declared
+/// inline in it
+fn make_private(ty: &mut Ty) {
+    if let Ty::Struct(fields) = ty {
+        for field in fields {
+            field.vis = None;
+            make_private(&mut field.ty);
+        }
+    }
+}
+
+/// whether `inline` is the type of a field of the struct `ty`, or of a struct declared in it
+fn declares(ty: &Ty, inline: &Ty) -> bool {
+    match ty {
+        Ty::Struct(fields) => fields
+            .iter()
+            .any(|field| &field.ty == inline || declares(&field.ty, inline)),
+        _ => false,
+    }
+}
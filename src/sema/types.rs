@@ -0,0 +1,222 @@
+use crate::{ast::FieldVis, sema::DefId};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Prim {
+    Iptr,
+    Uptr,
+    I8,
+    I16,
+    I24,
+    I32,
+    I64,
+    U8,
+    U16,
+    U24,
+    U32,
+    U64,
+    F32,
+    F64,
+    Char,
+    Str,
+    Bool,
+}
+
+impl Prim {
+    pub const ALL: [Self; 17] = [
+        Self::Iptr,
+        Self::Uptr,
+        Self::I8,
+        Self::I16,
+        Self::I24,
+        Self::I32,
+        Self::I64,
+        Self::U8,
+        Self::U16,
+        Self::U24,
+        Self::U32,
+        Self::U64,
+        Self::F32,
+        Self::F64,
+        Self::Char,
+        Self::Str,
+        Self::Bool,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Iptr => "iptr",
+            Self::Uptr => "uptr",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I24 => "i24",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U24 => "u24",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::Char => "char",
+            Self::Str => "str",
+            Self::Bool => "bool",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|prim| prim.name() == name)
+    }
+
+    /// the smallest and largest value of an integer type. pointer sized integers are assumed to
+    /// be 64 bits wide
+    pub fn int_range(self) -> Option<(i128, i128)> {
+        let signed = |bits: u32| (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1);
+        let unsigned = |bits: u32| (0, (1i128 << bits) - 1);
+
+        let range = match self {
+            Self::I8 => signed(8),
+            Self::I16 => signed(16),
+            Self::I24 => signed(24),
+            Self::I32 => signed(32),
+            Self::I64 | Self::Iptr => signed(64),
+            Self::U8 => unsigned(8),
+            Self::U16 => unsigned(16),
+            Self::U24 => unsigned(24),
+            Self::U32 => unsigned(32),
+            Self::U64 | Self::Uptr => unsigned(64),
+            _ => return None,
+        };
+
+        Some(range)
+    }
+
+    pub fn is_int(self) -> bool {
+        self.int_range().is_some()
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, Self::F32 | Self::F64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldTy {
+    pub name: String,
+    pub ty: Ty,
+    /// `None` for private fields, the fields of un-named structs are always public
+    pub vis: Option<FieldVis>,
+    pub has_default: bool,
+}
+
+/// a type declared with `type`, it is distinct from every other type including its
+/// representation
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedTy {
+    pub def: DefId,
+    pub name: String,
+    pub repr: Box<Ty>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ty {
+    /// the type of an expression that couldn't be checked, it is compatible with every type so
+    /// one error doesn't cause others
+    Unknown,
+    Prim(Prim),
+    Named(NamedTy),
+    Ref(Box<Ty>),
+    Fn(Box<Ty>, Box<Ty>),
+    /// `()` is the struct without fields
+    Struct(Vec<FieldTy>),
+    /// a value of exactly one of the variants, written as a struct with that one field
+    Union(Vec<FieldTy>),
+}
+
+impl Ty {
+    pub fn unit() -> Self {
+        Self::Struct(Vec::new())
+    }
+
+    pub fn is_unit(&self) -> bool {
+        matches!(self, Self::Struct(fields) if fields.is_empty())
+    }
+
+    /// whether a value of type `self` can be used where `other` is expected. struct fields are
+    /// matched by name, so their order doesn't matter
+    pub fn compatible(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Unknown, _) | (_, Self::Unknown) => true,
+            (Self::Named(a), Self::Named(b)) => a.def == b.def,
+            (Self::Ref(a), Self::Ref(b)) => a.compatible(b),
+            (Self::Fn(a_arg, a_ret), Self::Fn(b_arg, b_ret)) => {
+                a_arg.compatible(b_arg) && a_ret.compatible(b_ret)
+            }
+            (Self::Struct(a), Self::Struct(b)) | (Self::Union(a), Self::Union(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|a| b.iter().any(|b| a.name == b.name && a.ty.compatible(&b.ty)))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// the type a named type is represented as, other types represent themselves
+    pub fn repr(&self) -> &Self {
+        match self {
+            Self::Named(named) => &named.repr,
+            ty => ty,
+        }
+    }
+
+    /// whether a value of type `self` can be cast to `other` with `other::{value}`. a named type
+    /// can be cast to and from its representation, and structs with the same fields can be cast
+    /// to one another. visibility isn't considered
+    pub fn castable(&self, other: &Self) -> bool {
+        self.compatible(other)
+            || self.repr().compatible(other)
+            || self.compatible(other.repr())
+            || matches!(self.repr(), Self::Struct(_)) && self.repr().compatible(other.repr())
+    }
+
+    /// the fields of a struct or of a named struct
+    pub fn fields(&self) -> Option<&[FieldTy]> {
+        match self.repr() {
+            Self::Struct(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldTy> {
+        self.fields()?.iter().find(|field| field.name == name)
+    }
+}
+
+impl Display for Ty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown => f.write_str("{unknown}"),
+            Self::Prim(prim) => f.write_str(prim.name()),
+            Self::Named(named) => f.write_str(&named.name),
+            Self::Ref(ty) => write!(f, "{ty}&"),
+            Self::Fn(arg, ret) => write!(f, "{arg} -> {ret}"),
+            Self::Struct(fields) | Self::Union(fields) => {
+                if let Self::Union(_) = self {
+                    f.write_str("union ")?;
+                }
+
+                f.write_str("(")?;
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+
+                    write!(f, "{}: {}", field.name, field.ty)?;
+                }
+
+                f.write_str(")")
+            }
+        }
+    }
+}
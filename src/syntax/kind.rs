@@ -0,0 +1,111 @@
+use crate::lexer::TokenKind;
+use rowan::Language;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum NodeKind {
+    Src,
+
+    DocComment,
+    Bounds,
+    AliasDecl,
+    FnDecl,
+    IfaceAliasItem,
+    IfaceFnItem,
+    IfaceDecl,
+
+    Alias,
+    Block,
+    FieldVis,
+    StructField,
+    StructExpr,
+    UnaryExpr,
+    BinaryExpr,
+
+    BoolLit,
+    IntLit,
+    FloatLit,
+    ChrLit,
+    StrLit,
+    InterpolatedStr,
+
+    AssignStmt,
+    CtrlStmt,
+    IfStmt,
+    WhileStmt,
+}
+
+/// the kind of an element in the syntax tree, leaves are tokens straight from the lexer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SyntaxKind {
+    Token(TokenKind),
+    Node(NodeKind),
+}
+
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(self, Self::Token(kind) if kind.is_trivia())
+    }
+}
+
+const TOKEN_COUNT: u16 = TokenKind::Error as u16 + 1;
+const NODE_COUNT: u16 = NodeKind::WhileStmt as u16 + 1;
+
+impl From<TokenKind> for SyntaxKind {
+    fn from(value: TokenKind) -> Self {
+        Self::Token(value)
+    }
+}
+
+impl From<NodeKind> for SyntaxKind {
+    fn from(value: NodeKind) -> Self {
+        Self::Node(value)
+    }
+}
+
+impl From<SyntaxKind> for rowan::SyntaxKind {
+    fn from(value: SyntaxKind) -> Self {
+        match value {
+            SyntaxKind::Token(kind) => Self(kind as u16),
+            SyntaxKind::Node(kind) => Self(TOKEN_COUNT + kind as u16),
+        }
+    }
+}
+
+impl From<TokenKind> for rowan::SyntaxKind {
+    fn from(value: TokenKind) -> Self {
+        SyntaxKind::from(value).into()
+    }
+}
+
+impl From<NodeKind> for rowan::SyntaxKind {
+    fn from(value: NodeKind) -> Self {
+        SyntaxKind::from(value).into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Lang {}
+
+impl Language for Lang {
+    type Kind = SyntaxKind;
+
+    fn kind_from_raw(raw: rowan::SyntaxKind) -> Self::Kind {
+        let rowan::SyntaxKind(raw) = raw;
+        assert!(raw < TOKEN_COUNT + NODE_COUNT, "invalid syntax kind {raw}");
+
+        // both enums are fieldless and `repr(u16)` and the bounds are checked above
+        match raw.checked_sub(TOKEN_COUNT) {
+            None => SyntaxKind::Token(unsafe { std::mem::transmute::<u16, TokenKind>(raw) }),
+            Some(raw) => SyntaxKind::Node(unsafe { std::mem::transmute::<u16, NodeKind>(raw) }),
+        }
+    }
+
+    fn kind_to_raw(kind: Self::Kind) -> rowan::SyntaxKind {
+        kind.into()
+    }
+}
+
+pub type SyntaxNode = rowan::SyntaxNode<Lang>;
+pub type SyntaxToken = rowan::SyntaxToken<Lang>;
+pub type SyntaxElement = rowan::SyntaxElement<Lang>;
@@ -0,0 +1,5 @@
+mod kind;
+mod view;
+
+pub use kind::*;
+pub use view::*;
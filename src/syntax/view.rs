@@ -0,0 +1,480 @@
+use crate::{
+    ast::{self, AliasEval, BinOp, CtrlOp, PostOp},
+    lexer::TokenKind,
+    span::Span,
+    syntax::{NodeKind, SyntaxKind, SyntaxNode, SyntaxToken},
+};
+use rowan::Direction;
+use unicode_normalization::UnicodeNormalization;
+
+/// a typed view of a node in the syntax tree. views borrow nothing from the source, every
+/// accessor walks the underlying node
+pub trait AstView: Sized {
+    fn cast(node: SyntaxNode) -> Option<Self>;
+    fn syntax(&self) -> &SyntaxNode;
+
+    fn span(&self) -> Span {
+        self.syntax().text_range().into()
+    }
+}
+
+fn child<N: AstView>(node: &SyntaxNode) -> Option<N> {
+    node.children().find_map(N::cast)
+}
+
+fn children<N: AstView>(node: &SyntaxNode) -> impl Iterator<Item = N> {
+    node.children().filter_map(N::cast)
+}
+
+/// the tokens that are direct children of a node
+fn tokens(node: &SyntaxNode) -> impl Iterator<Item = SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+}
+
+fn token(node: &SyntaxNode, kind: TokenKind) -> Option<SyntaxToken> {
+    tokens(node).find(|token| token.kind() == SyntaxKind::Token(kind))
+}
+
+/// the first expression after a token, used where an alias precedes the expression
+fn expr_after(node: &SyntaxNode, kind: TokenKind) -> Option<Expr> {
+    node.children_with_tokens()
+        .skip_while(|element| element.kind() != SyntaxKind::Token(kind))
+        .filter_map(|element| element.into_node())
+        .find_map(Expr::cast)
+}
+
+/// the first token of a node that is not trivia
+fn first_token(node: &SyntaxNode) -> Option<SyntaxToken> {
+    tokens(node).find(|token| !token.kind().is_trivia())
+}
+
+macro_rules! node_views {
+    ($($name:ident),* $(,)?) => {$(
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $name(SyntaxNode);
+
+        impl AstView for $name {
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                match node.kind() == SyntaxKind::Node(NodeKind::$name) {
+                    true => Some(Self(node)),
+                    false => None,
+                }
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.0
+            }
+        }
+    )*};
+}
+
+macro_rules! enum_view {
+    ($name:ident { $($variant:ident($view:ident)),* $(,)? }) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant($view),)*
+        }
+
+        impl AstView for $name {
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                match node.kind() {
+                    $(SyntaxKind::Node(NodeKind::$view) => Some(Self::$variant($view(node))),)*
+                    _ => None,
+                }
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                match self {
+                    $(Self::$variant(view) => view.syntax(),)*
+                }
+            }
+        }
+    };
+}
+
+node_views! {
+    Src,
+    DocComment,
+    Bounds,
+    AliasDecl,
+    FnDecl,
+    IfaceAliasItem,
+    IfaceFnItem,
+    IfaceDecl,
+    Alias,
+    Block,
+    FieldVis,
+    StructField,
+    StructExpr,
+    UnaryExpr,
+    BinaryExpr,
+    BoolLit,
+    IntLit,
+    FloatLit,
+    ChrLit,
+    StrLit,
+    InterpolatedStr,
+    AssignStmt,
+    CtrlStmt,
+    IfStmt,
+    WhileStmt,
+}
+
+enum_view!(Decl {
+    Alias(AliasDecl),
+    Fn(FnDecl),
+    Iface(IfaceDecl),
+});
+
+enum_view!(IfaceItem {
+    Alias(IfaceAliasItem),
+    Fn(IfaceFnItem),
+});
+
+enum_view!(Expr {
+    Alias(Alias),
+    Block(Block),
+    Struct(StructExpr),
+    Bool(BoolLit),
+    Int(IntLit),
+    Float(FloatLit),
+    Chr(ChrLit),
+    Str(StrLit),
+    InterpolatedStr(InterpolatedStr),
+    IfStmt(IfStmt),
+    WhileStmt(WhileStmt),
+    UnaryExpr(UnaryExpr),
+    BinaryExpr(BinaryExpr),
+});
+
+enum_view!(Stmt {
+    Assign(AssignStmt),
+    Ctrl(CtrlStmt),
+    If(IfStmt),
+    While(WhileStmt),
+    AliasDecl(AliasDecl),
+    FnDecl(FnDecl),
+    IfaceDecl(IfaceDecl),
+});
+
+impl Src {
+    pub fn decls(&self) -> impl Iterator<Item = Decl> {
+        children(&self.0)
+    }
+}
+
+impl DocComment {
+    pub fn text(&self) -> String {
+        let lines = tokens(&self.0)
+            .filter(|token| token.kind() == SyntaxKind::Token(TokenKind::DocComment))
+            .map(|token| {
+                let text = &token.text()[2..];
+                text.strip_prefix(' ').unwrap_or(text).to_owned()
+            })
+            .collect::<Vec<_>>();
+
+        lines.join("\n")
+    }
+}
+
+impl Bounds {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl AliasDecl {
+    pub fn doc(&self) -> Option<DocComment> {
+        child(&self.0)
+    }
+
+    pub fn eval(&self) -> Option<AliasEval> {
+        alias_eval(&self.0)
+    }
+
+    pub fn alias(&self) -> Option<Alias> {
+        child(&self.0)
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        child(&self.0)
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        expr_after(&self.0, TokenKind::Eq)
+    }
+}
+
+impl FnDecl {
+    pub fn doc(&self) -> Option<DocComment> {
+        child(&self.0)
+    }
+
+    /// the alias after `::` for methods, the only alias otherwise
+    pub fn alias(&self) -> Option<Alias> {
+        children(&self.0).nth(usize::from(self.owner().is_some()))
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+
+    /// the type a method is declared on, `T` in `fn T::name`
+    pub fn owner(&self) -> Option<Alias> {
+        token(&self.0, TokenKind::ColonColon)?;
+        child(&self.0)
+    }
+}
+
+impl IfaceAliasItem {
+    pub fn doc(&self) -> Option<DocComment> {
+        child(&self.0)
+    }
+
+    pub fn eval(&self) -> Option<AliasEval> {
+        alias_eval(&self.0)
+    }
+
+    pub fn alias(&self) -> Option<Alias> {
+        child(&self.0)
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        child(&self.0)
+    }
+
+    pub fn default(&self) -> Option<Expr> {
+        expr_after(&self.0, TokenKind::Eq)
+    }
+}
+
+impl IfaceFnItem {
+    pub fn doc(&self) -> Option<DocComment> {
+        child(&self.0)
+    }
+
+    pub fn alias(&self) -> Option<Alias> {
+        child(&self.0)
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        child(&self.0)
+    }
+
+    pub fn default(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+impl IfaceDecl {
+    pub fn doc(&self) -> Option<DocComment> {
+        child(&self.0)
+    }
+
+    pub fn alias(&self) -> Option<Alias> {
+        child(&self.0)
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = IfaceItem> {
+        children(&self.0)
+    }
+}
+
+fn alias_eval(node: &SyntaxNode) -> Option<AliasEval> {
+    let eval = match first_token(node)?.kind() {
+        SyntaxKind::Token(TokenKind::KwLet) => AliasEval::Let,
+        SyntaxKind::Token(TokenKind::KwVar) => AliasEval::Var,
+        SyntaxKind::Token(TokenKind::KwConst) => AliasEval::Const,
+        SyntaxKind::Token(TokenKind::KwType) => AliasEval::Type,
+        _ => return None,
+    };
+
+    Some(eval)
+}
+
+impl Alias {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        token(&self.0, TokenKind::Alias)
+    }
+
+    /// the NFC normalized name, the same string the AST holds
+    pub fn name(&self) -> Option<String> {
+        self.token().map(|token| token.text().nfc().collect())
+    }
+}
+
+impl Block {
+    /// every statement, excluding the trailing expression
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt> {
+        let tail = self.expr().map(|expr| expr.syntax().clone());
+        self.0
+            .children()
+            .filter(move |node| Some(node) != tail.as_ref())
+            .filter_map(Stmt::cast)
+    }
+
+    /// the trailing expression, the last child that is not followed by a ';'
+    pub fn expr(&self) -> Option<Expr> {
+        let last = self.0.children().last()?;
+        let terminated = last
+            .siblings_with_tokens(Direction::Next)
+            .any(|element| element.kind() == SyntaxKind::Token(TokenKind::Semi));
+
+        match terminated {
+            true => None,
+            false => Expr::cast(last),
+        }
+    }
+}
+
+impl FieldVis {
+    pub fn vis(&self) -> ast::FieldVis {
+        match token(&self.0, TokenKind::Colon) {
+            Some(_) => ast::FieldVis::PubGet,
+            None => ast::FieldVis::Pub,
+        }
+    }
+}
+
+impl StructField {
+    pub fn doc(&self) -> Option<DocComment> {
+        child(&self.0)
+    }
+
+    pub fn vis(&self) -> Option<ast::FieldVis> {
+        child::<FieldVis>(&self.0).map(|vis| vis.vis())
+    }
+
+    pub fn alias(&self) -> Option<Alias> {
+        child(&self.0)
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        child(&self.0)
+    }
+
+    pub fn val(&self) -> Option<Expr> {
+        expr_after(&self.0, TokenKind::Eq)
+    }
+}
+
+impl StructExpr {
+    pub fn default_vis(&self) -> Option<ast::FieldVis> {
+        child::<FieldVis>(&self.0).map(|vis| vis.vis())
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = StructField> {
+        children(&self.0)
+    }
+}
+
+impl UnaryExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn op(&self) -> Option<PostOp> {
+        tokens(&self.0).find_map(|token| match token.kind() {
+            SyntaxKind::Token(TokenKind::Amp) => Some(PostOp::Ref),
+            SyntaxKind::Token(TokenKind::Star) => Some(PostOp::Deref),
+            SyntaxKind::Token(TokenKind::Question) => Some(PostOp::Unwrap),
+            _ => None,
+        })
+    }
+}
+
+impl BinaryExpr {
+    pub fn lhs(&self) -> Option<Expr> {
+        children(&self.0).next()
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+
+    pub fn op(&self) -> Option<BinOp> {
+        tokens(&self.0).find_map(|token| match token.kind() {
+            SyntaxKind::Token(TokenKind::ColonColon) => Some(BinOp::EvalPath),
+            SyntaxKind::Token(TokenKind::Dot) => Some(BinOp::ExecPath),
+            SyntaxKind::Token(TokenKind::Arrow) => Some(BinOp::FnArrow),
+            SyntaxKind::Token(TokenKind::Gt) => Some(BinOp::PipeR),
+            SyntaxKind::Token(TokenKind::Lt) => Some(BinOp::PipeL),
+            _ => None,
+        })
+    }
+}
+
+macro_rules! lit_token {
+    ($($name:ident),* $(,)?) => {$(
+        impl $name {
+            pub fn token(&self) -> Option<SyntaxToken> {
+                first_token(&self.0)
+            }
+        }
+    )*};
+}
+
+lit_token!(BoolLit, IntLit, FloatLit, ChrLit, StrLit);
+
+impl InterpolatedStr {
+    pub fn exprs(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}
+
+impl AssignStmt {
+    pub fn lhs(&self) -> Option<Expr> {
+        children(&self.0).next()
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl CtrlStmt {
+    pub fn op(&self) -> Option<CtrlOp> {
+        let op = match first_token(&self.0)?.kind() {
+            SyntaxKind::Token(TokenKind::KwReturn) => CtrlOp::Return,
+            SyntaxKind::Token(TokenKind::KwDefer) => CtrlOp::Defer,
+            SyntaxKind::Token(TokenKind::KwContinue) => CtrlOp::Continue,
+            SyntaxKind::Token(TokenKind::KwBreak) => CtrlOp::Break,
+            _ => return None,
+        };
+
+        Some(op)
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl IfStmt {
+    pub fn lhs(&self) -> Option<Expr> {
+        children(&self.0).next()
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+
+    pub fn chain(&self) -> Option<Expr> {
+        children(&self.0).nth(2)
+    }
+}
+
+impl WhileStmt {
+    pub fn lhs(&self) -> Option<Expr> {
+        children(&self.0).next()
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
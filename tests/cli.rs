@@ -0,0 +1,108 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+const BIN: &str = env!("CARGO_BIN_EXE_rename_me");
+
+/// a fresh directory for the files of one test
+fn scratch(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(dir: &Path, name: &str, src: &str) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, src).unwrap();
+    path
+}
+
+fn run(args: &[&str], paths: &[&Path]) -> Output {
+    Command::new(BIN).args(args).args(paths).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn one_file_is_dumped_without_its_path() {
+    let dir = scratch("dump_one");
+    let a = write(&dir, "a.idk", "const a = 1;\n");
+
+    let output = run(&["dump-ast", "--format", "sexpr"], &[&a]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "((AliasDecl 0..11 :eval const :alias \"a\" :rhs (IntLit 10..11 :neg false :val 1)))\n"
+    );
+}
+
+#[test]
+fn several_files_are_dumped_after_their_paths() {
+    let dir = scratch("dump_several");
+    let a = write(&dir, "a.idk", "const a = 1;\n");
+    let b = write(&dir, "b.idk", "fn b {}\n");
+
+    let output = run(&["dump-ast", "--format", "sexpr"], &[&a, &b]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let lines: Vec<_> = stdout(&output).lines().map(str::to_string).collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(&format!("{}: ((AliasDecl", a.display())));
+    assert!(lines[1].starts_with(&format!("{}: ((FnDecl", b.display())));
+}
+
+#[test]
+fn files_with_errors_exit_with_1() {
+    let dir = scratch("dump_errors");
+    let good = write(&dir, "good.idk", "const a = 1;\n");
+    let bad = write(&dir, "bad.idk", "const = 1;\n");
+
+    let output = run(&["dump-ast"], &[&good, &bad]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output).lines().count(), 1);
+    assert!(!output.stderr.is_empty());
+
+    let output = run(
+        &["check"],
+        &[&write(&dir, "ty.idk", "const a: bool = 1;\n")],
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn unreadable_files_exit_with_2() {
+    let dir = scratch("dump_missing");
+    let output = run(&["dump-ast"], &[&dir.join("missing.idk")]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = run(&["dump-ast", "--format", "xml"], &[&dir]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn closed_stdout_stops_the_dump_quietly() {
+    let dir = scratch("dump_closed");
+    let src = "const a = 1;\n".repeat(2000);
+    let paths: Vec<_> = (0..50)
+        .map(|i| write(&dir, &format!("{i}.idk"), &src))
+        .collect();
+
+    let mut child = Command::new(BIN)
+        .arg("dump-ast")
+        .args(&paths)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    drop(child.stdout.take());
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+}
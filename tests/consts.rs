@@ -0,0 +1,100 @@
+use rename_me::{parse_source, sema};
+
+fn analyze(src: &str) -> sema::Analysis {
+    sema::analyze(&parse_source(src).expect("the source should parse"))
+}
+
+/// the value of every constant, in source order
+fn consts(src: &str) -> Vec<String> {
+    let analysis = analyze(src);
+    assert!(
+        analysis.errors.is_empty(),
+        "unexpected errors: {:?}",
+        analysis.errors
+    );
+
+    let mut consts: Vec<_> = analysis
+        .consts
+        .values
+        .iter()
+        .map(|(id, val)| {
+            let def = analysis.res.def(*id);
+            (def.span, format!("{} = {val}", def.name))
+        })
+        .collect();
+
+    consts.sort_by_key(|(span, _)| span.map(|span| span.start));
+    consts.into_iter().map(|(_, val)| val).collect()
+}
+
+fn errors(src: &str) -> Vec<String> {
+    analyze(src)
+        .errors
+        .into_iter()
+        .map(|err| err.message)
+        .collect()
+}
+
+#[test]
+fn constants_are_folded() {
+    assert_eq!(
+        consts(
+            "const a = 1;
+const b = (x = a, y = $\"{a}\");
+const c = b.x;
+const d = if false { 0 } else { c };"
+        ),
+        ["a = 1", "b = (x = 1, y = \"1\")", "c = 1", "d = 1"]
+    );
+}
+
+#[test]
+fn constants_can_be_used_before_they_are_declared() {
+    assert_eq!(
+        consts("const a = b;\nconst b = 'b';"),
+        ["a = 'b'", "b = 'b'"]
+    );
+}
+
+#[test]
+fn calls_are_folded() {
+    assert_eq!(
+        consts(
+            "fn id: i32 -> i32 { arg }
+fn unit { arg }
+fn ret: () { arg }
+const a = 3 > id;
+const b = () > unit;
+const c = () > ret;"
+        ),
+        ["a = 3", "b = ()", "c = ()"]
+    );
+}
+
+#[test]
+fn cycles_are_detected() {
+    let analysis = analyze("const a = b;\nconst b = a;\nconst c = 1;");
+    let [err] = analysis.errors.as_slice() else {
+        panic!("expected one error, got {:?}", analysis.errors);
+    };
+
+    assert_eq!(err.message, "cycle detected when evaluating `a`");
+    let labels: Vec<_> = err.labels.iter().map(|label| &label.message).collect();
+    assert_eq!(labels, ["`a` uses `b`", "`b` uses `a`"]);
+}
+
+#[test]
+fn cycles_through_fns_are_detected() {
+    assert_eq!(
+        errors("const a = () > f;\nfn f { a }"),
+        ["cycle detected when evaluating `a`"]
+    );
+}
+
+#[test]
+fn runaway_calls_stop() {
+    assert_eq!(
+        errors("fn forever: i32 -> i32 { arg > forever }\nconst a = 1 > forever;"),
+        ["too many nested calls"]
+    );
+}
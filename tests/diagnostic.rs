@@ -0,0 +1,90 @@
+//! diagnostics from the public parsing API and the ways the driver shows them
+
+use rename_me::{
+    check_source,
+    diagnostic::{Diagnostic, Severity},
+    parse_source,
+    span::Span,
+};
+
+#[test]
+fn parse_errors_are_diagnostics_with_spans() {
+    let src = "const a = 1;\nconst = 2;";
+    let errors = parse_source(src).unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].severity, Severity::Error);
+    assert_eq!(errors[0].message, "expected Alias, found `=`");
+    assert_eq!(errors[0].span.line_col(src), (2, 7));
+}
+
+#[test]
+fn check_source_reports_semantic_errors() {
+    let src = "fn f {\n\tlet a = 1;\n\ta = 2;\n}";
+    let errors = check_source(src).unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    let rendered = errors[0].render("f.idk", src, false);
+    assert_eq!(
+        rendered,
+        "error: cannot assign to `a`, it is declared with `let`, use `var` to make it mutable
+ --> f.idk:3:2
+  |
+3 | \ta = 2;
+  | \t^^^^^
+ --> f.idk:2:6
+  |
+2 | \tlet a = 1;
+  | \t    - `a` is declared here
+"
+    );
+
+    assert!(check_source("fn f {\n\tvar a = 1;\n\ta = 2;\n}").is_ok());
+}
+
+#[test]
+fn the_gutter_fits_the_widest_line_number() {
+    let src = format!("{}const a = b;", "\n".repeat(9));
+    let err = Diagnostic::error("unknown", Span { start: 19, end: 20 })
+        .with_label(Span { start: 0, end: 0 }, "start");
+
+    assert_eq!(
+        err.render("a.idk", &src, false),
+        "error: unknown
+  --> a.idk:10:11
+   |
+10 | const a = b;
+   |           ^
+  --> a.idk:1:1
+   |
+ 1 | 
+   | - start
+"
+    );
+}
+
+#[test]
+fn colors_are_only_written_when_enabled() {
+    let src = "const a = b;";
+    let err = Diagnostic::warning("unused", Span { start: 6, end: 7 });
+
+    let plain = err.render("a.idk", src, false);
+    assert!(!plain.contains('\x1b'));
+    assert!(plain.starts_with("warning: unused\n"));
+
+    let colored = err.render("a.idk", src, true);
+    assert!(colored.starts_with("\x1b[1;33mwarning\x1b[0m\x1b[1m: unused\x1b[0m\n"));
+    assert!(colored.contains("\x1b[1;33m^\x1b[0m"));
+}
+
+#[test]
+fn json_diagnostics_have_one_based_locations() {
+    let src = "const a = 1;\nconst a = \"\\\"\";";
+    let err = Diagnostic::error("duplicate", Span { start: 19, end: 20 })
+        .with_label(Span { start: 6, end: 7 }, "first `a`");
+
+    assert_eq!(
+        err.to_json("dir/a.idk", src),
+        r#"{"severity":"error","message":"duplicate","path":"dir/a.idk","span":[19,20],"line":2,"column":7,"labels":[{"message":"first `a`","span":[6,7],"line":1,"column":7}]}"#
+    );
+}
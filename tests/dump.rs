@@ -0,0 +1,66 @@
+//! the JSON and S-expression dumps are a stable interface for external tools, these tests pin
+//! their exact output
+
+use rename_me::{
+    ast::{to_json, to_sexpr, Dump},
+    parse_source,
+};
+
+const SRC: &str = "## doc
+const a: f64 = 1.5;
+type T = (pub:get x: i32 = -1);
+fn T::f: Self -> str { $\"a\\\"{b}\\t\" }
+";
+
+#[test]
+fn json_dumps_every_field_with_spans() {
+    let file = parse_source(SRC).unwrap();
+    let expected = [
+        r#"[{"kind":"AliasDecl","span":[0,25],"doc":"doc","eval":"const","alias":"a","#,
+        r#""bounds":{"kind":"Alias","span":[16,19],"alias":"f64"},"#,
+        r#""rhs":{"kind":"FloatLit","span":[22,25],"val":1.5}},"#,
+        r#"{"kind":"AliasDecl","span":[27,57],"doc":null,"eval":"type","alias":"T","bounds":null,"#,
+        r#""rhs":{"kind":"StructExpr","span":[36,57],"default_vis":null,"fields":["#,
+        r#"{"kind":"StructField","span":[37,56],"doc":null,"vis":"pub_get","alias":"x","#,
+        r#""bounds":{"kind":"Alias","span":[48,51],"alias":"i32"},"#,
+        r#""val":{"kind":"IntLit","span":[54,56],"neg":true,"val":1}}]}},"#,
+        r#"{"kind":"FnDecl","span":[59,95],"doc":null,"alias":"f","#,
+        r#""bounds":{"kind":"BinaryExpr","span":[68,79],"#,
+        r#""lhs":{"kind":"Alias","span":[68,72],"alias":"Self"},"#,
+        r#""rhs":{"kind":"Alias","span":[76,79],"alias":"str"},"op":"fn_arrow"},"#,
+        r#""body":{"kind":"Block","span":[80,95],"stmts":[],"#,
+        r#""expr":{"kind":"InterpolatedStr","span":[82,93],"#,
+        r#""parts":["a\"",{"kind":"Alias","span":[88,89],"alias":"b"},"\t"]}},"owner":"T"}]"#,
+    ];
+
+    assert_eq!(to_json(&file.decls), expected.concat());
+}
+
+#[test]
+fn sexprs_leave_out_absent_fields() {
+    let file = parse_source(SRC).unwrap();
+    let expected = [
+        r#"((AliasDecl 0..25 :doc "doc" :eval const :alias "a" :bounds (Alias 16..19 :alias "f64") "#,
+        r#":rhs (FloatLit 22..25 :val 1.5)) "#,
+        r#"(AliasDecl 27..57 :eval type :alias "T" :rhs (StructExpr 36..57 :fields "#,
+        r#"((StructField 37..56 :vis pub_get :alias "x" :bounds (Alias 48..51 :alias "i32") "#,
+        r#":val (IntLit 54..56 :neg true :val 1))))) "#,
+        r#"(FnDecl 59..95 :alias "f" :bounds (BinaryExpr 68..79 :lhs (Alias 68..72 :alias "Self") "#,
+        r#":rhs (Alias 76..79 :alias "str") :op fn_arrow) :body (Block 80..95 :stmts () "#,
+        r#":expr (InterpolatedStr 82..93 :parts ("a\"" (Alias 88..89 :alias "b") "\t"))) "#,
+        r#":owner "T"))"#,
+    ];
+
+    assert_eq!(to_sexpr(&file.decls), expected.concat());
+}
+
+#[test]
+fn strings_are_escaped_for_json() {
+    let file = parse_source("const s = \"q\\\"b\\\\n\\nt\\tc\u{1}\";").unwrap();
+    let [decl] = &file.decls[..] else {
+        panic!("expected one decl");
+    };
+
+    let json = decl.dump().to_json();
+    assert!(json.contains(r#""val":"q\"b\\n\nt\tc\u0001""#), "{json}");
+}
@@ -0,0 +1,105 @@
+use rename_me::{fmt::format_src, parse_source};
+use std::{fs, path::Path};
+
+fn tour_sources() -> Vec<(String, String)> {
+    let tour = Path::new(env!("CARGO_MANIFEST_DIR")).join("tour");
+    let mut dirs = vec![tour];
+    let mut sources = Vec::new();
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "idk") {
+                let src = fs::read_to_string(&path).unwrap();
+                sources.push((path.display().to_string(), src));
+            }
+        }
+    }
+
+    sources.sort();
+    sources
+}
+
+/// the debug output of the AST with every span removed, formatting must not change it
+fn ast(src: &str) -> String {
+    let mut out = format!("{:?}", parse_source(src).unwrap().decls);
+    while let Some(start) = out.find("span: Span {") {
+        let end = start + out[start..].find('}').unwrap() + 1;
+        out.replace_range(start..end, "");
+    }
+
+    out
+}
+
+fn assert_formats(src: &str, expected: &str) {
+    assert_eq!(format_src(src).unwrap(), expected);
+    assert_eq!(format_src(expected).unwrap(), expected);
+}
+
+#[test]
+fn formatting_is_idempotent_and_keeps_the_ast() {
+    let mut formatted_files = 0;
+    for (path, src) in tour_sources() {
+        let Ok(formatted) = format_src(&src) else {
+            continue;
+        };
+
+        assert_eq!(
+            format_src(&formatted).unwrap(),
+            formatted,
+            "formatting {path} twice changes it"
+        );
+        assert_eq!(
+            ast(&formatted),
+            ast(&src),
+            "formatting {path} changes its ast"
+        );
+        formatted_files += 1;
+    }
+
+    assert!(formatted_files > 0);
+}
+
+#[test]
+fn short_blocks_stay_on_one_line() {
+    assert_formats(
+        "const num: Num = Num::{ int };",
+        "const num: Num = Num::{int};\n",
+    );
+    assert_formats(
+        "fn f {\n\tlet a = if true {\n\t\t1\n\t} else {\n\t\t2\n\t};\n}",
+        "fn f {\n\tlet a = if true { 1 } else { 2 };\n}\n",
+    );
+    assert_formats(
+        "fn f {\n\tlet a = if x { 1 } else if y { 2 } else { 3 };\n}",
+        "fn f {\n\tlet a = if x { 1 } else if y { 2 } else { 3 };\n}\n",
+    );
+}
+
+#[test]
+fn fn_bodies_and_blocks_with_statements_are_broken() {
+    assert_formats("fn f { 1 }", "fn f {\n\t1\n}\n");
+    assert_formats(
+        "fn f { let a = if x { let b = 1; b } else { 2 }; }",
+        "fn f {\n\tlet a = if x {\n\t\tlet b = 1;\n\t\tb\n\t} else {\n\t\t2\n\t};\n}\n",
+    );
+}
+
+#[test]
+fn long_blocks_are_broken() {
+    let long = "a_really_long_function_name_that_goes_on > another_really_long_function_name";
+    assert_formats(
+        &format!("fn f {{ let a = if x {{ {long} }} else {{ 2 }}; }}"),
+        &format!("fn f {{\n\tlet a = if x {{\n\t\t{long}\n\t}} else {{\n\t\t2\n\t}};\n}}\n"),
+    );
+}
+
+#[test]
+fn comments_are_kept() {
+    assert_formats(
+        "const a = 1 # one\n;\n\n\n# two\nconst b = 2",
+        "const a = 1; # one\n\n# two\nconst b = 2;\n",
+    );
+}
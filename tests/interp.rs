@@ -0,0 +1,191 @@
+use rename_me::{diagnostic::Diagnostic, eval, parse_source, sema};
+
+/// runs a program that has no compile time errors, its result is the value `main` returns
+fn run(src: &str) -> Result<String, Diagnostic> {
+    let file = parse_source(src).expect("the source should parse");
+    let analysis = sema::analyze(&file);
+    let errors: Vec<_> = analysis.errors.iter().map(|err| &err.message).collect();
+    assert!(errors.is_empty(), "the source should check: {errors:?}");
+
+    eval::run(&file, &analysis).map(|val| val.to_string())
+}
+
+/// the message of a runtime error and the source it points at
+fn run_err(src: &str) -> (String, &str) {
+    let err = run(src).expect_err("the program should fail");
+    (err.message, &src[err.span.start..err.span.end])
+}
+
+#[test]
+fn main_returns_the_tail_of_its_block() {
+    assert_eq!(
+        run("fn main: () -> i32 {\n\tlet a = 1;\n\ta\n}").unwrap(),
+        "1"
+    );
+    assert_eq!(run("fn main {\n\tlet a = 1;\n}").unwrap(), "()");
+}
+
+#[test]
+fn nested_blocks_and_ifs_are_values() {
+    let src = "fn main: () -> str {
+\tlet a = if false { \"a\" } else if true { \"b\" } else { \"c\" };
+\t$\"{a}{if true { 1 } else { 2 }}\"
+}";
+
+    assert_eq!(run(src).unwrap(), "\"b1\"");
+}
+
+#[test]
+fn while_loops_run_until_their_condition_is_false() {
+    let src = "fn main: () -> str {
+\tvar log = \"\";
+\tvar go = true;
+\twhile go {
+\t\tlog = $\"{log}x\";
+\t\tgo = false;
+\t};
+\tlog
+}";
+
+    assert_eq!(run(src).unwrap(), "\"x\"");
+}
+
+#[test]
+fn break_and_continue_leave_the_loop_body() {
+    let src = "fn main: () -> str {
+\tvar log = \"\";
+\tvar first = true;
+\twhile true {
+\t\tif first {
+\t\t\tfirst = false;
+\t\t\tlog = $\"{log}c\";
+\t\t\tcontinue;
+\t\t};
+\t\tlog = $\"{log}b\";
+\t\tbreak;
+\t\tlog = $\"{log}!\";
+\t};
+\tlog
+}";
+
+    assert_eq!(run(src).unwrap(), "\"cb\"");
+}
+
+#[test]
+fn return_leaves_the_fn_early() {
+    let src = "fn pick: bool -> str {
+\tif arg {
+\t\treturn \"early\";
+\t};
+\t\"late\"
+}
+fn main: () -> str {
+\tlet a = true > pick;
+\tlet b = false > pick;
+\t$\"{a} {b}\"
+}";
+
+    assert_eq!(run(src).unwrap(), "\"early late\"");
+}
+
+#[test]
+fn defers_run_in_reverse_order_when_a_block_is_left_early() {
+    let src = "fn work: str& -> () {
+\tdefer { arg* = $\"{arg*}1\"; };
+\tdefer { arg* = $\"{arg*}2\"; };
+\tif true {
+\t\tdefer { arg* = $\"{arg*}3\"; };
+\t\treturn;
+\t};
+\targ* = $\"{arg*}!\";
+}
+fn main: () -> str {
+\tvar log = \"\";
+\tlet done = log& > work;
+\tlog
+}";
+
+    assert_eq!(run(src).unwrap(), "\"321\"");
+}
+
+#[test]
+fn defers_run_when_a_loop_is_broken() {
+    let src = "fn main: () -> str {
+\tvar log = \"\";
+\twhile true {
+\t\tdefer { log = $\"{log}d\"; };
+\t\tlog = $\"{log}b\";
+\t\tbreak;
+\t};
+\tlog
+}";
+
+    assert_eq!(run(src).unwrap(), "\"bd\"");
+}
+
+#[test]
+fn structs_are_constructed_with_their_defaults() {
+    let src = "type P = (pub x: i32, pub y: i32 = 2, pub z: str = \"z\");
+fn main: () -> P {
+\tvar p = P::(x = 1);
+\tp.z = \"w\";
+\tp
+}";
+
+    assert_eq!(run(src).unwrap(), "(x = 1, y = 2, z = \"w\")");
+}
+
+#[test]
+fn pipes_call_fns_in_both_directions() {
+    let src = "fn wrap: str -> str { $\"({arg})\" }
+fn main: () -> str {
+\tlet right = \"a\" > wrap > wrap;
+\tlet left = wrap < \"b\";
+\t$\"{right} {left}\"
+}";
+
+    assert_eq!(run(src).unwrap(), "\"((a)) (b)\"");
+}
+
+#[test]
+fn curried_fns_take_their_receiver_first() {
+    let src = "type P = (pub x: i32);
+fn P::with: Self -> i32 -> Self {
+\tself.x = arg;
+\tself
+}
+fn main: () -> i32 {
+\tlet set = P::(x = 1) > P::with;
+\tlet p = 2 > set;
+\tp.x
+}";
+
+    assert_eq!(run(src).unwrap(), "2");
+}
+
+#[test]
+fn runtime_errors_point_at_their_cause() {
+    let src = "fn main {\n\tlet a = (x = 1);\n\tlet b = a?;\n}";
+    assert_eq!(
+        run_err(src),
+        ("unwrapping can't be evaluated yet".into(), "a?")
+    );
+}
+
+#[test]
+fn runaway_recursion_is_stopped() {
+    let src = "fn forever: i32 -> i32 {\n\targ > forever\n}\nfn main {\n\tlet a = 1 > forever;\n}";
+    assert_eq!(
+        run_err(src),
+        ("too many nested calls".into(), "arg > forever")
+    );
+}
+
+#[test]
+fn programs_without_main_are_rejected() {
+    let src = "fn start {}";
+    assert_eq!(
+        run(src).unwrap_err().message,
+        "`main` isn't declared, programs start by calling it"
+    );
+}
@@ -0,0 +1,271 @@
+use rename_me::{
+    ast::{Decl, Dump, Expr},
+    lexer::{Lexer, TokenKind, KEYWORDS},
+    parse_source, sema,
+};
+
+/// the tokens of `src` that aren't whitespace, with their text
+fn tokens(src: &str) -> Vec<(TokenKind, &str)> {
+    let (tokens, errors) = Lexer::tokenize(src);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    tokens
+        .into_iter()
+        .filter(|token| token.kind != TokenKind::Whitespace)
+        .map(|token| (token.kind, &src[token.span.start..token.span.end]))
+        .collect()
+}
+
+/// the s-expression of the value of `const s = {lit}`
+fn const_val(lit: &str) -> String {
+    let src = format!("const s = {lit};");
+    let file = parse_source(&src).expect("the literal should parse");
+    match &file.decls[0] {
+        Decl::Alias(decl) => decl.rhs.dump().to_sexpr(),
+        decl => panic!("expected a const, found {decl:?}"),
+    }
+}
+
+/// the value of the string literal `lit`
+fn str_val(lit: &str) -> String {
+    let src = format!("const s = {lit};");
+    let file = parse_source(&src).expect("the literal should parse");
+    match &file.decls[0] {
+        Decl::Alias(decl) => match &decl.rhs {
+            Expr::Str(lit) => lit.val.clone(),
+            rhs => panic!("expected a string literal, found {rhs:?}"),
+        },
+        decl => panic!("expected a const, found {decl:?}"),
+    }
+}
+
+/// every error in `src`, with the offset it points at and the text it covers
+fn errors(src: &str) -> Vec<(String, usize, &str)> {
+    parse_source(src)
+        .expect_err("the source shouldn't parse")
+        .into_iter()
+        .map(|err| {
+            (
+                err.message,
+                err.span.start,
+                &src[err.span.start..err.span.end],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn raw_strings_keep_escapes_and_quotes() {
+    assert_eq!(str_val(r#"r"a\nb\""#), r"a\nb\");
+    assert_eq!(str_val(r###"r##"say "#hi"#"##"###), r##"say "#hi"#"##);
+}
+
+#[test]
+fn unterminated_raw_strings_point_at_the_opening_delimiter() {
+    let src = "const s = r#\"abc\";";
+    assert_eq!(
+        errors(src),
+        [("unterminated string literal".into(), 10, "r#\"")]
+    );
+}
+
+#[test]
+fn multi_line_strings_strip_the_indentation_of_the_closing_delimiter() {
+    let lit = "\"\"\"\n\t\thello\n\t\t  \n\t\t  world \\\"\\t\\\"\n\t\t\"\"\"";
+    assert_eq!(str_val(lit), "hello\n\n  world \"\t\"");
+
+    assert_eq!(str_val("\"\"\"\r\n\tline\r\n\t\"\"\""), "line");
+    assert_eq!(str_val("\"\"\"\n\"\"\""), "");
+}
+
+#[test]
+fn multi_line_string_delimiters_must_be_on_their_own_lines() {
+    let msg = "the delimiters of a multi-line string must be on their own lines";
+    assert_eq!(
+        errors("const s = \"\"\"abc\n\"\"\";"),
+        [(msg.into(), 10, "\"\"\"")]
+    );
+    assert_eq!(
+        errors("const s = \"\"\"\n\tabc\"\"\";"),
+        [(msg.into(), 18, "\"\"\"")]
+    );
+}
+
+#[test]
+fn multi_line_string_lines_must_be_indented() {
+    let src = "const s = \"\"\"\n\t\tabc\n\tx\n\t\t\"\"\";";
+    let msg =
+        "lines in a multi-line string must be indented at least as far as the closing delimiter";
+    assert_eq!(errors(src), [(msg.into(), src.find('x').unwrap(), "")]);
+}
+
+#[test]
+fn unterminated_strings_point_at_the_opening_quote() {
+    let msg = "unterminated string literal";
+    assert_eq!(errors("const s = \"abc"), [(msg.into(), 10, "\"")]);
+    assert_eq!(
+        errors("const s = \"\"\"\nabc"),
+        [(msg.into(), 10, "\"\"\"")]
+    );
+}
+
+#[test]
+fn unknown_escapes_are_rejected() {
+    assert_eq!(
+        errors("const s = \"a\\qb\";"),
+        [("unknown escape sequence".into(), 12, "\\q")]
+    );
+}
+
+#[test]
+fn interpolated_strings_alternate_text_and_expressions() {
+    assert_eq!(
+        const_val(r#"$"a{b}\{c\}{ {d} }""#),
+        "(InterpolatedStr 10..29 :parts (\"a\" (Alias 14..15 :alias \"b\") \"{c}\" \
+         (Block 23..26 :stmts () :expr (Alias 24..25 :alias \"d\"))))"
+    );
+}
+
+#[test]
+fn interpolated_strings_can_be_nested() {
+    assert_eq!(
+        const_val(r#"$"{$"x{y}"}z""#),
+        "(InterpolatedStr 10..23 :parts ((InterpolatedStr 13..20 :parts (\"x\" \
+         (Alias 17..18 :alias \"y\"))) \"z\"))"
+    );
+}
+
+#[test]
+fn unmatched_braces_in_interpolated_strings_must_be_escaped() {
+    assert_eq!(
+        errors("const s = $\"a}\";"),
+        [(
+            "unmatched `}` in interpolated string, escape it with `\\}`".into(),
+            13,
+            "}"
+        )]
+    );
+}
+
+#[test]
+fn unterminated_interpolated_strings_point_at_the_opening_delimiter() {
+    let msg = "unterminated string literal";
+    assert_eq!(errors("const s = $\"a"), [(msg.into(), 10, "$\"")]);
+
+    // the quote inside the expression starts a new string
+    assert_eq!(
+        errors("const s = $\"a{b\";"),
+        [(msg.into(), 15, "\""), (msg.into(), 10, "$\"")]
+    );
+}
+
+#[test]
+fn block_comments_nest() {
+    assert_eq!(
+        tokens("#[ a #[ b ]# c ]# x #[]#"),
+        [
+            (TokenKind::BlockComment, "#[ a #[ b ]# c ]#"),
+            (TokenKind::Alias, "x"),
+            (TokenKind::BlockComment, "#[]#"),
+        ]
+    );
+
+    assert_eq!(
+        const_val("#[ one ]# 1 #[ two ]#"),
+        "(IntLit 20..21 :neg false :val 1)"
+    );
+}
+
+#[test]
+fn line_comments_are_docs_only_with_two_hashes() {
+    assert_eq!(
+        tokens("# a\n## b\n### c\n"),
+        [
+            (TokenKind::Comment, "# a"),
+            (TokenKind::DocComment, "## b"),
+            (TokenKind::Comment, "### c"),
+        ]
+    );
+}
+
+#[test]
+fn unterminated_block_comments_point_at_the_outermost_opening() {
+    assert_eq!(
+        errors("const a = 1;\n#[ a #[ b ]#\n"),
+        [("unterminated block comment".into(), 13, "#[")]
+    );
+}
+
+#[test]
+fn identifiers_follow_the_xid_rules() {
+    assert_eq!(
+        tokens("café _x __y αβγ 变量 a1 a-1 a->b"),
+        [
+            (TokenKind::Alias, "café"),
+            (TokenKind::Alias, "_x"),
+            (TokenKind::Alias, "__y"),
+            (TokenKind::Alias, "αβγ"),
+            (TokenKind::Alias, "变量"),
+            (TokenKind::Alias, "a1"),
+            (TokenKind::Alias, "a"),
+            (TokenKind::Int, "-1"),
+            (TokenKind::Alias, "a"),
+            (TokenKind::Arrow, "->"),
+            (TokenKind::Alias, "b"),
+        ]
+    );
+}
+
+#[test]
+fn identifiers_must_start_with_a_letter_after_underscores() {
+    let msg = "unexpected character";
+    assert_eq!(errors("const _ = 1;"), [(msg.into(), 6, "_")]);
+    assert_eq!(errors("const __1 = 1;"), [(msg.into(), 6, "__")]);
+    assert_eq!(errors("const a = 😀;"), [(msg.into(), 10, "😀")]);
+}
+
+#[test]
+fn identifiers_are_normalized_to_nfc() {
+    let decomposed = "cafe\u{301}";
+    assert_eq!(const_val(decomposed), "(Alias 10..16 :alias \"caf\u{e9}\")");
+
+    let src = format!("const caf\u{e9} = 1;\nconst b = {decomposed};");
+    assert!(sema::check(&parse_source(&src).unwrap()).is_empty());
+}
+
+#[test]
+fn keywords_cannot_be_declared() {
+    for (keyword, _) in KEYWORDS {
+        let src = format!("const {keyword} = 1;");
+        assert_eq!(
+            errors(&src),
+            [(format!("`{keyword}` is a reserved keyword"), 6, *keyword)]
+        );
+    }
+}
+
+#[test]
+fn keywords_are_rejected_wherever_a_name_is_expected() {
+    let msg = |keyword: &str| format!("`{keyword}` is a reserved keyword");
+    assert_eq!(
+        errors("fn f: i32::(while) {}"),
+        [(msg("while"), 12, "while")]
+    );
+    assert_eq!(
+        errors("type T = (pub break: i32);"),
+        [(msg("break"), 14, "break")]
+    );
+    assert_eq!(errors("const a = (x = 1).fn;"), [(msg("fn"), 18, "fn")]);
+}
+
+#[test]
+fn names_can_start_with_keywords() {
+    assert_eq!(
+        tokens("lets iface_ fnord"),
+        [
+            (TokenKind::Alias, "lets"),
+            (TokenKind::Alias, "iface_"),
+            (TokenKind::Alias, "fnord"),
+        ]
+    );
+}
@@ -0,0 +1,13 @@
+//! the pest grammar the hand-written parser replaced, extended with the struct, `->`, `iface` and
+//! `pub:get` rules the hand-written parser gained later so both parsers accept the same language
+
+use pest::Parser;
+
+#[derive(pest_derive::Parser)]
+#[grammar = "../tests/old_grammar/grammar.pest"]
+pub struct PestParser;
+
+/// whether the old grammar accepts `src` as a source file
+pub fn accepts(src: &str) -> bool {
+    PestParser::parse(Rule::src, src).is_ok()
+}
@@ -0,0 +1,281 @@
+//! every tour file is parsed and compared with its snapshot in `tests/snapshots/tour`, one
+//! s-expression per decl or one line per error. run with `UPDATE_SNAPSHOTS=1` to rewrite the
+//! snapshots after an intended change. the old pest grammar in `tests/old_grammar` is the
+//! reference for which sources are accepted at all
+
+mod old_grammar;
+
+use rename_me::{ast::Dump, fmt::format_src, parse_source, parser::Parser, span::Span};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+fn idk_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            idk_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "idk") {
+            files.push(path);
+        }
+    }
+}
+
+fn snapshot(src: &str) -> String {
+    match parse_source(src) {
+        Ok(file) => file
+            .decls
+            .iter()
+            .map(|decl| decl.dump().to_sexpr() + "\n")
+            .collect(),
+        Err(errors) => errors
+            .iter()
+            .map(|err| {
+                let (line, col) = err.span.line_col(src);
+                format!("error {line}:{col}: {}\n", err.message)
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn tour_files_match_snapshots() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let tour = root.join("tour");
+    let snapshots = root.join("tests/snapshots/tour");
+    let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+
+    let mut files = Vec::new();
+    idk_files(&tour, &mut files);
+    files.sort();
+    assert!(!files.is_empty(), "no tour files found");
+
+    let mut mismatched = Vec::new();
+    for path in files {
+        let name = path.strip_prefix(&tour).unwrap().to_string_lossy();
+        let snapshot_path = snapshots.join(name.replace('/', "-").replace(".idk", ".txt"));
+        let actual = snapshot(&fs::read_to_string(&path).unwrap());
+
+        if update {
+            fs::write(&snapshot_path, actual).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&snapshot_path) {
+            Ok(expected) if expected == actual => {}
+            _ => mismatched.push(name.into_owned()),
+        }
+    }
+
+    assert!(
+        mismatched.is_empty(),
+        "parsing doesn't match the snapshots of {mismatched:?}, rerun with UPDATE_SNAPSHOTS=1 if \
+         the change is intended"
+    );
+}
+
+/// sources both parsers accept or both reject
+const AGREED: &[&str] = &[
+    "const a = 1;",
+    "const a = 1; type T = i32; fn f {} fn g {}",
+    "type P = (pub x: i32, pub:get y: i32 = 2);",
+    "type Q = (pub *, a, b: str);",
+    "type Q = (pub:get *);",
+    "const a = ();",
+    "const a = (x,);",
+    "const a = (pub x);",
+    "const a = (## a field\n x);",
+    "fn f: i32 -> i32 { arg > g }",
+    "fn f { var a = 1; a = 2; x.y = 1; while a < 3 { break; }; }",
+    "fn f { let a = { 1 }; }",
+    "fn f { if a { 1 } else { 2 } }",
+    "fn f { defer { x = 1; }; return 1; }",
+    "const a = x&*?;",
+    "const a = T::{x};",
+    "const a = T::(x = 1);",
+    "const a = a->b;",
+    "const a = true; const b = ' '; const c = '\\n';",
+    "const a = -0x1f; const b = 1.5; const c = 0b101; const d = 0o7; const e = 1.;",
+    "const s = r#\"a\"b\"#;",
+    "const s = \"\"\"\n\tx\n\t\"\"\";",
+    "const s = $\"a{b > c}\\{\";",
+    "## doc\nconst a = 1;",
+    "#[ a #[ b ]# ]# const a = 1;",
+    "const a = caf\u{e9};",
+    "iface I { const A: Self; fn f: Self -> (); fn g { 1 } }",
+    "iface I { fn f }",
+    "const = 1;",
+    "const a = ;",
+    "const a = 1;;",
+    "const a = 1 +",
+    "const a = (1, 2",
+    "const a = (,);",
+    "fn f",
+    "fn f { 1; }",
+    "fn f { continue }",
+    "fn f { fn g {} }",
+    "fn f { iface I {} }",
+    "iface I { iface J {} }",
+    "const if = 1;",
+    "const a = _1;",
+    "const a = a-1;",
+    "const a = .5;",
+    "const a = 1e5;",
+    "const a = 0x;",
+    "const a = '';",
+    "const a = 'ab';",
+    "const a = \"abc",
+    "const a = \"\\q\";",
+    "const s = \"\"\"x\n\"\"\";",
+    "const s = $\"}\";",
+    "#[ unterminated",
+];
+
+/// sources the parsers disagree on on purpose, with whether the new parser accepts them
+const CHANGED: &[(&str, bool)] = &[
+    // only const, type and fn are allowed at the top level
+    ("let a = 1;", false),
+    ("var a = 1", false),
+    // the lines of a multi-line string must be indented as far as its closing delimiter
+    ("const s = \"\"\"\n\tx\nx\n\t\"\"\";", false),
+    // docs with nothing to document are plain comments
+    ("## stray doc", true),
+    ("fn f { ## doc\n x = 1; }", true),
+    ("const a = 1 ## doc\n;", true),
+];
+
+#[test]
+fn tour_files_are_accepted_like_the_old_grammar() {
+    let mut files = Vec::new();
+    idk_files(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tour"),
+        &mut files,
+    );
+    assert!(!files.is_empty(), "no tour files found");
+
+    for path in files {
+        let src = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            parse_source(&src).is_ok(),
+            old_grammar::accepts(&src),
+            "{} is accepted by only one parser",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn sources_are_accepted_like_the_old_grammar() {
+    for src in AGREED {
+        assert_eq!(
+            parse_source(src).is_ok(),
+            old_grammar::accepts(src),
+            "{src:?} is accepted by only one parser"
+        );
+    }
+
+    for &(src, accepted) in CHANGED {
+        assert_eq!(old_grammar::accepts(src), !accepted, "{src:?}");
+        assert_eq!(parse_source(src).is_ok(), accepted, "{src:?}");
+    }
+}
+
+#[test]
+fn docs_are_attached_to_decls_fields_and_iface_items() {
+    let src = "## a const
+const a = 1;
+type T = (
+\t## a field
+\tpub b: i32,
+);
+iface I {
+\t## an item
+\tfn c;
+}
+fn f {
+\t## a local
+\tlet d = 1;
+}
+";
+
+    let decls = snapshot(src);
+    for doc in ["a const", "a field", "an item", "a local"] {
+        assert!(
+            decls.contains(&format!(":doc \"{doc}\"")),
+            "{doc:?} isn't attached"
+        );
+    }
+}
+
+#[test]
+fn docs_with_nothing_to_document_are_comments() {
+    let src = "fn f {
+\t## before a statement
+\tvar a = 1;
+\t## before an assignment
+\ta = 2;
+\tlet b = ## inside an expression
+\t3;
+\t## before the end of a block
+}
+type T = (
+\tpub a: i32,
+\t## before the end of a struct
+);
+## at the end of the file
+";
+
+    let parse = Parser::parse(src).expect("stray docs should be comments");
+    assert_eq!(parse.syntax().to_string(), src);
+
+    let decls = snapshot(src);
+    assert_eq!(decls.matches(":doc").count(), 1, "{decls}");
+    assert!(decls.contains(":doc \"before a statement\""));
+
+    assert_eq!(format_src(src).unwrap(), src);
+}
+
+#[test]
+fn variables_cannot_be_declared_at_the_top_level() {
+    let msg = "variables can't be declared in the top level scope, use `const` instead";
+    for (src, decl) in [
+        ("const a = 1;\nlet b = 2;", "let b = 2"),
+        ("var c = 3", "var c = 3"),
+    ] {
+        let errors = parse_source(src).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, msg);
+        assert_eq!(&src[errors[0].span.start..errors[0].span.end], decl);
+    }
+
+    assert!(parse_source("fn f {\n\tlet b = 2;\n\tvar c = 3;\n}").is_ok());
+}
+
+#[test]
+fn source_files_hold_every_top_level_decl() {
+    let src = "\n# leading\nconst a = 1\ntype T = i32;\nfn f {};\niface I {}\n# trailing\n";
+    let file = parse_source(src).unwrap();
+
+    assert_eq!(file.decls.len(), 4);
+    assert_eq!(
+        file.span,
+        Span {
+            start: 0,
+            end: src.len()
+        }
+    );
+    assert!(file.path.is_none());
+
+    assert!(parse_source("").unwrap().decls.is_empty());
+}
+
+#[test]
+fn source_files_end_after_the_last_decl() {
+    let src = "const a = 1;\n}";
+    let errors = parse_source(src).unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "expected Decl, found `}`");
+    assert_eq!(errors[0].span.start, src.len() - 1);
+}
@@ -0,0 +1,195 @@
+//! random edits are applied to the tour files that parse, both incrementally and by parsing the
+//! edited source from scratch, and the two results are compared
+
+use rename_me::{
+    ast::to_sexpr,
+    parser::{Parse, Parser},
+    reparse::TextEdit,
+    span::Span,
+};
+use std::{fs, path::Path};
+
+const EDITS_PER_FILE: usize = 400;
+
+/// snippets inserted by the edits, chosen to open and close blocks, split tokens and add trivia
+const SNIPPETS: &[&str] = &[
+    "",
+    "x",
+    "_y",
+    "1",
+    "-2",
+    "0.5",
+    ";",
+    ",",
+    " ",
+    "\n",
+    "\t",
+    "{",
+    "}",
+    "(",
+    ")",
+    "{ 1 }",
+    "let a = 1;",
+    "var b = a;",
+    "const c = 2;",
+    "a = b;",
+    "return;",
+    "# note\n",
+    "## doc\n",
+    "#[ block ]#",
+    "\"s\"",
+    "'c'",
+    "if true { 1 } else { 2 }",
+    "while false {};",
+    "x > f",
+    "f < x",
+    "T::(a = 1)",
+    "T::{a}",
+    "a.b",
+    "x&",
+    "x*",
+    "fn g { }",
+    "type T = (a: i32);",
+];
+
+/// a small xorshift generator, so every run makes the same edits
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn char_boundary(src: &str, mut pos: usize) -> usize {
+    while !src.is_char_boundary(pos) {
+        pos -= 1;
+    }
+
+    pos
+}
+
+fn random_edit(rng: &mut Rng, src: &str) -> TextEdit {
+    let start = char_boundary(src, rng.below(src.len() + 1));
+    let len = match rng.below(4) {
+        0 => 0,
+        1 => rng.below(4),
+        _ => rng.below(24),
+    };
+
+    let end = char_boundary(src, (start + len).min(src.len()));
+    let text = match rng.below(4) {
+        // text copied from elsewhere in the file keeps the edits close to real code
+        0 => {
+            let from = char_boundary(src, rng.below(src.len() + 1));
+            let to = char_boundary(src, (from + rng.below(32)).min(src.len()));
+            src[from..to].to_string()
+        }
+        _ => SNIPPETS[rng.below(SNIPPETS.len())].to_string(),
+    };
+
+    TextEdit {
+        span: Span::new(start, end),
+        text,
+    }
+}
+
+fn assert_same(incremental: &Parse, full: &Parse, src: &str, edit: &TextEdit) {
+    let context = || {
+        format!(
+            "after replacing {:?} with {:?} in\n{src}",
+            edit.span, edit.text
+        )
+    };
+
+    assert_eq!(
+        incremental.green,
+        full.green,
+        "syntax trees differ {}",
+        context()
+    );
+    assert_eq!(
+        incremental.syntax().to_string(),
+        src,
+        "syntax tree text differs {}",
+        context()
+    );
+    assert_eq!(
+        incremental.file.span,
+        full.file.span,
+        "file spans differ {}",
+        context()
+    );
+    assert_eq!(
+        to_sexpr(&incremental.file.decls),
+        to_sexpr(&full.file.decls),
+        "asts differ {}",
+        context()
+    );
+    assert_eq!(
+        format!("{:?}", incremental.file.decls),
+        format!("{:?}", full.file.decls),
+        "asts differ {}",
+        context()
+    );
+}
+
+#[test]
+fn random_edits_match_a_full_reparse() {
+    let tour = Path::new(env!("CARGO_MANIFEST_DIR")).join("tour");
+    let mut paths: Vec<_> = fs::read_dir(&tour)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "idk"))
+        .collect();
+
+    paths.sort();
+
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut checked = 0;
+    for path in paths {
+        let mut src = fs::read_to_string(&path).unwrap();
+        let Ok(mut parse) = Parser::parse(&src) else {
+            continue;
+        };
+
+        for _ in 0..EDITS_PER_FILE {
+            let edit = random_edit(&mut rng, &src);
+            let mut edited = src.clone();
+            edit.apply(&mut edited);
+
+            match (parse.reparse(&edit), Parser::parse(&edited)) {
+                (Ok(()), Ok(full)) => {
+                    assert_same(&parse, &full, &edited, &edit);
+                    src = edited;
+                    checked += 1;
+                }
+                (Err(_), Err(_)) => {
+                    assert_eq!(
+                        parse.syntax().to_string(),
+                        src,
+                        "a failed reparse changed the tree"
+                    );
+                }
+                (Ok(()), Err(err)) => panic!(
+                    "incremental reparse accepted {:?} replaced with {:?}, a full parse reports \
+                     {err:?}",
+                    edit.span, edit.text
+                ),
+                (Err(err), Ok(_)) => panic!(
+                    "incremental reparse rejected {:?} replaced with {:?}: {err:?}",
+                    edit.span, edit.text
+                ),
+            }
+        }
+    }
+
+    assert!(checked > 100, "only {checked} edits parsed");
+}
@@ -0,0 +1,168 @@
+use rename_me::repl::{is_incomplete, Session};
+
+/// evaluates every input in one session, errors are printed as their message
+fn run(inputs: &[&str]) -> Vec<String> {
+    let mut session = Session::new();
+    inputs
+        .iter()
+        .flat_map(|input| session.eval(input))
+        .map(|line| match line {
+            Ok(line) => line,
+            Err(err) => format!("error: {}", err.message),
+        })
+        .collect()
+}
+
+#[test]
+fn bindings_are_kept_across_inputs() {
+    assert_eq!(
+        run(&["let a = 1", "var b = a", "b = 5; b", "a"]),
+        ["a: i32 = 1", "b: i32 = 1", "5: i32", "1: i32"]
+    );
+}
+
+#[test]
+fn annotations_are_checked() {
+    assert_eq!(
+        run(&["let x: i64 = \"hello\"", "x", "let y: i64 = 2", "y"]),
+        [
+            "error: expected `i64`, found `str`",
+            "error: cannot find `x` in this scope",
+            "y: i64 = 2",
+            "2: i64",
+        ]
+    );
+}
+
+#[test]
+fn assignments_keep_the_type_of_the_binding() {
+    assert_eq!(
+        run(&["var z = 1", "z = \"s\"", "z", "let c = 1", "c = 2"]),
+        [
+            "z: i32 = 1",
+            "error: expected `i32`, found `str`",
+            "1: i32",
+            "c: i32 = 1",
+            "error: cannot assign to `c`, it is declared with `let`, use `var` to make it mutable",
+        ]
+    );
+}
+
+#[test]
+fn names_cant_be_declared_twice() {
+    assert_eq!(
+        run(&["let a = 1", "let a = 2", "a"]),
+        [
+            "a: i32 = 1",
+            "error: `a` is already declared in this scope",
+            "1: i32"
+        ]
+    );
+}
+
+#[test]
+fn control_flow_is_evaluated() {
+    assert_eq!(
+        run(&[
+            "var n = 0",
+            "var go = true",
+            "while go { n = 3; go = false; };",
+            "n",
+            "if go { \"yes\" } else { \"no\" }",
+        ]),
+        ["n: i32 = 0", "go: bool = true", "3: i32", "\"no\": str"]
+    );
+}
+
+#[test]
+fn structs_casts_and_calls_are_evaluated() {
+    assert_eq!(
+        run(&[
+            "type P = (pub x: i32, pub y: i32 = 2)",
+            "let p = P::(x = 1)",
+            "p.y",
+            "type Num = i32",
+            "Num::{p.x}",
+            "fn pick: bool -> i32 { if arg { 1 } else { 2 } }",
+            "false > pick",
+        ]),
+        [
+            "type P",
+            "p: P = (x = 1, y = 2)",
+            "2: i32",
+            "type Num",
+            "1: Num",
+            "fn pick",
+            "2: i32",
+        ]
+    );
+}
+
+#[test]
+fn references_share_their_binding() {
+    assert_eq!(
+        run(&["var v = 1", "let r = v&", "v = 2", "r*"]),
+        ["v: i32 = 1", "r: i32& = 1&", "2: i32"]
+    );
+}
+
+#[test]
+fn constants_are_evaluated() {
+    assert_eq!(
+        run(&["const c = 4", "const d: i32& = c&", "d*"]),
+        ["c: i32 = 4", "d: i32& = 4&", "4: i32"]
+    );
+}
+
+#[test]
+fn runtime_errors_keep_earlier_statements() {
+    assert_eq!(
+        run(&[
+            "fn forever: i32 -> i32 { arg > forever }",
+            "let k = 3; let f = 1 > forever",
+            "k",
+            "return;",
+        ]),
+        [
+            "fn forever",
+            "k: i32 = 3",
+            "error: too many nested calls",
+            "3: i32",
+            "error: `return` can't be used outside of a fn",
+        ]
+    );
+}
+
+#[test]
+fn diagnostics_point_into_the_session_source() {
+    let mut session = Session::new();
+    session.eval("let a = 1\n");
+    let errors = session.eval("let b: bool = a\n");
+
+    let Some(Err(err)) = errors.first() else {
+        panic!("expected an error, got {errors:?}");
+    };
+
+    assert_eq!(&session.src()[err.span.as_range()], "a");
+    assert_eq!(err.span.line_col(session.src()), (2, 15));
+}
+
+#[test]
+fn open_delimiters_need_more_input() {
+    assert!(is_incomplete("fn f {"));
+    assert!(is_incomplete("let p = (a = 1,"));
+    assert!(is_incomplete("let s = \"open"));
+    assert!(is_incomplete("#[ comment"));
+    assert!(!is_incomplete("fn f { 1 }"));
+}
+
+#[test]
+fn casts_put_fields_in_the_order_of_the_type() {
+    assert_eq!(
+        run(&[
+            "type PubCoords = (pub lat: f64, pub lng: f64)",
+            "PubCoords::{(lng = 1.5, lat = 2.5)}",
+        ]),
+        ["type PubCoords", "(lat = 2.5, lng = 1.5): PubCoords"]
+    );
+}
@@ -0,0 +1,152 @@
+//! name resolution links every alias to the def it refers to, through file, fn and block scopes
+
+use rename_me::{
+    ast::SourceFile,
+    parse_source,
+    sema::{resolve, Def, DefKind, Resolution},
+    span::Span,
+};
+
+fn parse(src: &str) -> SourceFile {
+    parse_source(src).expect("the source should parse")
+}
+
+/// the def of the alias `name` at the first occurrence of `at`, which starts with the alias
+fn def_at<'a>(res: &'a Resolution, src: &str, at: &str, name: &str) -> &'a Def {
+    let start = src
+        .find(at)
+        .unwrap_or_else(|| panic!("`{at}` doesn't occur"));
+    let span = Span {
+        start,
+        end: start + name.len(),
+    };
+
+    res.lookup(span)
+        .unwrap_or_else(|| panic!("`{name}` at {start} isn't resolved"))
+}
+
+/// every error with the text it points at, and the text of its first label
+fn errors(src: &str) -> Vec<(String, &str, Option<&str>)> {
+    let (_, errors) = resolve(&parse(src));
+    errors
+        .into_iter()
+        .map(|err| {
+            let label = err
+                .labels
+                .first()
+                .map(|label| &src[label.span.start..label.span.end]);
+            (err.message, &src[err.span.start..err.span.end], label)
+        })
+        .collect()
+}
+
+#[test]
+fn uses_resolve_to_their_decls() {
+    let src = "const not_var = 0;\nfn f {\n\tvar a = not_var;\n\tlet b = a&;\n}";
+    let (res, errors) = resolve(&parse(src));
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let decl = def_at(&res, src, "not_var;", "not_var");
+    assert_eq!(decl.kind, DefKind::Const);
+    assert_eq!(decl.span, Some(Span { start: 6, end: 13 }));
+
+    let var = def_at(&res, src, "a&", "a");
+    assert_eq!(var.kind, DefKind::Var);
+    assert_eq!(var.span.map(|span| span.start), src.find("a = not_var"));
+}
+
+#[test]
+fn items_are_visible_before_their_decls_but_locals_are_not() {
+    let src = "fn f: () -> T { g }\nfn g {}\ntype T = i32;";
+    assert_eq!(errors(src), []);
+
+    let src = "fn f {\n\tlet a = b;\n\tlet b = 1;\n}";
+    assert_eq!(
+        errors(src),
+        [("cannot find `b` in this scope".into(), "b", None)]
+    );
+}
+
+#[test]
+fn blocks_close_their_scope() {
+    let src = "fn f {\n\tlet a = { let b = 1; b };\n\tlet c = b;\n}";
+    assert_eq!(
+        errors(src),
+        [("cannot find `b` in this scope".into(), "b", None)]
+    );
+}
+
+#[test]
+fn fns_bind_arg_self_and_self_types() {
+    let src = "type P = (x: i32);
+fn one: i32 -> i32 { arg }
+fn two: i32 -> i32 -> i32 { self }
+fn P::get: Self -> () -> i32 { self.x }
+iface I {
+\tfn id: Self -> Self;
+}";
+    let (res, errors) = resolve(&parse(src));
+    assert!(errors.is_empty(), "{errors:?}");
+
+    assert_eq!(def_at(&res, src, "arg }", "arg").kind, DefKind::Arg);
+    assert_eq!(def_at(&res, src, "self }", "self").kind, DefKind::SelfArg);
+    assert_eq!(def_at(&res, src, "self.x", "self").kind, DefKind::SelfArg);
+
+    let method_self = def_at(&res, src, "Self -> ()", "Self");
+    assert_eq!(method_self.kind, DefKind::Type);
+    assert_eq!(method_self.name, "P");
+
+    assert_eq!(
+        def_at(&res, src, "Self -> Self", "Self").kind,
+        DefKind::SelfType
+    );
+}
+
+#[test]
+fn implicit_names_only_exist_where_they_are_bound() {
+    let src = "fn f { self }\nconst a = Self;";
+    assert_eq!(
+        errors(src),
+        [
+            ("cannot find `self` in this scope".into(), "self", None),
+            ("cannot find `Self` in this scope".into(), "Self", None),
+        ]
+    );
+}
+
+#[test]
+fn names_cannot_be_declared_twice_in_a_scope() {
+    let src = "const a = 1;\nfn a {}";
+    assert_eq!(
+        errors(src),
+        [(
+            "`a` is already declared in this scope".into(),
+            "a",
+            Some("a")
+        )]
+    );
+}
+
+#[test]
+fn locals_cannot_shadow_names_of_the_same_fn() {
+    let src = "fn f {\n\tlet a = 1;\n\tif true {\n\t\tlet a = 2;\n\t};\n}";
+    let (_, errs) = resolve(&parse(src));
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].message, "`a` shadows an existing name");
+    assert_eq!(errs[0].span.start, src.rfind("a =").unwrap());
+    assert_eq!(errs[0].labels[0].span.start, src.find("a =").unwrap());
+
+    let src = "fn f: i32 -> i32 {\n\tlet arg = 1;\n\targ\n}";
+    assert_eq!(
+        errors(src),
+        [("`arg` shadows an existing name".into(), "arg", Some("i32"))]
+    );
+}
+
+#[test]
+fn locals_can_shadow_names_from_outside_the_fn() {
+    assert_eq!(
+        errors("const a = 1;\nfn f {\n\tlet a = 2;\n\tlet i32 = 3;\n}"),
+        []
+    );
+}
@@ -0,0 +1,168 @@
+//! the syntax tree keeps every token of the source, and its typed views read the same structure
+//! the AST is built with
+
+use rename_me::{
+    ast::{AliasEval, BinOp, CtrlOp, FieldVis, PostOp},
+    lexer::TokenKind,
+    parser::Parser,
+    syntax::{AstView, Decl, Expr, IfaceItem, Src, Stmt, SyntaxKind},
+};
+use std::{fs, path::Path};
+
+fn src(text: &str) -> Src {
+    let parse = Parser::parse(text).expect("the source should parse");
+    Src::cast(parse.syntax()).expect("the root should be a source file")
+}
+
+#[test]
+fn tour_files_round_trip_exactly() {
+    let tour = Path::new(env!("CARGO_MANIFEST_DIR")).join("tour");
+    let mut parsed = 0;
+
+    for entry in fs::read_dir(tour).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "idk") {
+            continue;
+        }
+
+        let text = fs::read_to_string(&path).unwrap();
+        if let Ok(parse) = Parser::parse(&text) {
+            assert_eq!(parse.syntax().to_string(), text, "{}", path.display());
+            parsed += 1;
+        }
+    }
+
+    assert!(parsed > 0, "no tour file parses");
+}
+
+#[test]
+fn trivia_is_kept_as_tokens() {
+    let text = "# a comment\nconst a = #[ inline ]# 1 ;\n\n";
+    let root = src(text);
+    assert_eq!(root.syntax().to_string(), text);
+
+    let trivia: Vec<_> = root
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind().is_trivia())
+        .map(|token| (token.kind(), token.text().to_string()))
+        .collect();
+
+    assert_eq!(
+        trivia,
+        [
+            (SyntaxKind::Token(TokenKind::Comment), "# a comment".into()),
+            (SyntaxKind::Token(TokenKind::Whitespace), "\n".into()),
+            (SyntaxKind::Token(TokenKind::Whitespace), " ".into()),
+            (SyntaxKind::Token(TokenKind::Whitespace), " ".into()),
+            (SyntaxKind::Token(TokenKind::Whitespace), " ".into()),
+            (
+                SyntaxKind::Token(TokenKind::BlockComment),
+                "#[ inline ]#".into()
+            ),
+            (SyntaxKind::Token(TokenKind::Whitespace), " ".into()),
+            (SyntaxKind::Token(TokenKind::Whitespace), " ".into()),
+            (SyntaxKind::Token(TokenKind::Whitespace), "\n\n".into()),
+        ]
+    );
+}
+
+#[test]
+fn views_read_decls() {
+    let root = src("## one\n## two\nconst a: i32 = 1;\nfn T::f: Self -> i32 { 1 }\n");
+    let decls: Vec<_> = root.decls().collect();
+    assert_eq!(decls.len(), 2);
+
+    let Decl::Alias(decl) = &decls[0] else {
+        panic!("expected an alias decl, found {:?}", decls[0]);
+    };
+    assert_eq!(decl.doc().unwrap().text(), "one\ntwo");
+    assert!(matches!(decl.eval(), Some(AliasEval::Const)));
+    assert_eq!(decl.alias().unwrap().name().as_deref(), Some("a"));
+    assert_eq!(decl.bounds().unwrap().syntax().to_string(), ": i32");
+    assert_eq!(decl.rhs().unwrap().syntax().to_string(), "1");
+
+    let Decl::Fn(decl) = &decls[1] else {
+        panic!("expected a fn decl, found {:?}", decls[1]);
+    };
+    assert!(decl.doc().is_none());
+    assert_eq!(decl.owner().unwrap().name().as_deref(), Some("T"));
+    assert_eq!(decl.alias().unwrap().name().as_deref(), Some("f"));
+    assert_eq!(decl.body().unwrap().syntax().to_string(), "{ 1 }");
+}
+
+#[test]
+fn views_read_blocks_and_exprs() {
+    let root = src("fn f {\n\tx.y = a&;\n\treturn;\n\tb > g\n}");
+    let Some(Decl::Fn(decl)) = root.decls().next() else {
+        panic!("expected a fn decl");
+    };
+    let body = decl.body().unwrap();
+
+    let stmts: Vec<_> = body.stmts().collect();
+    assert_eq!(stmts.len(), 2);
+
+    let Stmt::Assign(assign) = &stmts[0] else {
+        panic!("expected an assignment, found {:?}", stmts[0]);
+    };
+    let Some(Expr::BinaryExpr(lhs)) = assign.lhs() else {
+        panic!("expected a path on the left");
+    };
+    assert!(matches!(lhs.op(), Some(BinOp::ExecPath)));
+    let Some(Expr::UnaryExpr(rhs)) = assign.rhs() else {
+        panic!("expected a reference on the right");
+    };
+    assert!(matches!(rhs.op(), Some(PostOp::Ref)));
+
+    let Stmt::Ctrl(ctrl) = &stmts[1] else {
+        panic!("expected a return, found {:?}", stmts[1]);
+    };
+    assert!(matches!(ctrl.op(), Some(CtrlOp::Return)));
+    assert!(ctrl.rhs().is_none());
+
+    let Some(Expr::BinaryExpr(tail)) = body.expr() else {
+        panic!("expected a pipe as the tail");
+    };
+    assert!(matches!(tail.op(), Some(BinOp::PipeR)));
+    assert_eq!(tail.span().start, 28);
+}
+
+#[test]
+fn views_read_structs_and_ifaces() {
+    let root = src("type T = (pub:get *, a: i32 = 1, pub b);\niface I { type A; fn f {} }");
+    let decls: Vec<_> = root.decls().collect();
+
+    let Decl::Alias(decl) = &decls[0] else {
+        panic!("expected an alias decl, found {:?}", decls[0]);
+    };
+    let Some(Expr::Struct(expr)) = decl.rhs() else {
+        panic!("expected a struct");
+    };
+    assert_eq!(expr.default_vis(), Some(FieldVis::PubGet));
+
+    let fields: Vec<_> = expr
+        .fields()
+        .map(|field| {
+            (
+                field.vis(),
+                field.alias().unwrap().name().unwrap(),
+                field.val().map(|val| val.syntax().to_string()),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            (None, "a".into(), Some("1".into())),
+            (Some(FieldVis::Pub), "b".into(), None),
+        ]
+    );
+
+    let Decl::Iface(decl) = &decls[1] else {
+        panic!("expected an iface, found {:?}", decls[1]);
+    };
+    let items: Vec<_> = decl.items().collect();
+    assert!(matches!(&items[0], IfaceItem::Alias(item) if item.default().is_none()));
+    assert!(matches!(&items[1], IfaceItem::Fn(item) if item.default().is_some()));
+}
@@ -0,0 +1,481 @@
+use rename_me::{
+    diagnostic::Diagnostic,
+    parse_source,
+    sema::{self, DefId},
+};
+
+fn diagnostics(src: &str) -> Vec<Diagnostic> {
+    sema::check(&parse_source(src).expect("the source should parse"))
+}
+
+fn errors(src: &str) -> Vec<String> {
+    diagnostics(src)
+        .into_iter()
+        .map(|err| err.message)
+        .collect()
+}
+
+/// the type of the binding declared as `name`
+fn ty(src: &str, name: &str) -> String {
+    let analysis = sema::analyze(&parse_source(src).expect("the source should parse"));
+    let id = analysis
+        .res
+        .defs
+        .iter()
+        .position(|def| def.name == name && def.span.is_some())
+        .unwrap_or_else(|| panic!("`{name}` isn't declared"));
+
+    analysis.types.values[&DefId(id)].to_string()
+}
+
+#[test]
+fn annotations_are_checked() {
+    assert_eq!(
+        errors("const a: i32 = \"s\";\nconst b: bool = 1;\nconst c: str = 'c';"),
+        [
+            "expected `i32`, found `str`",
+            "expected `bool`, found `i32`",
+            "expected `str`, found `char`",
+        ]
+    );
+    assert!(errors("const a: i64 = 1;\nconst b: f64 = 1.5;\nconst c: char = 'c';").is_empty());
+}
+
+#[test]
+fn mismatches_point_at_the_annotation() {
+    let src = "fn f {\n\tlet a: bool = 1;\n}";
+    let errors = diagnostics(src);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(&src[errors[0].span.as_range()], "1");
+    assert_eq!(&src[errors[0].labels[0].span.as_range()], "bool");
+    assert_eq!(errors[0].labels[0].message, "expected because of this");
+}
+
+#[test]
+fn int_literals_must_fit_their_type() {
+    assert_eq!(
+        errors("const a: u8 = 300;\nconst b: u8 = 255;\nconst c: i8 = -129;"),
+        ["`300` doesn't fit in `u8`", "`-129` doesn't fit in `i8`"]
+    );
+}
+
+#[test]
+fn fn_returns_are_checked() {
+    assert_eq!(
+        errors("fn f: () -> i32 {\n\t\"x\"\n}\nfn g: () -> bool {\n\treturn 1;\n}"),
+        [
+            "expected `i32`, found `str`",
+            "expected `bool`, found `i32`"
+        ]
+    );
+    assert!(errors("fn f: () -> i32 {\n\treturn 1;\n}\nfn g: () -> u8 {\n\t2\n}").is_empty());
+}
+
+#[test]
+fn if_and_else_must_agree() {
+    assert_eq!(
+        errors("fn f {\n\tlet a = if true { 1 } else { \"s\" };\n}"),
+        ["`if` and `else` have different types, expected `i32`, found `str`"]
+    );
+
+    let src = "fn f {\n\tlet a = if true { 1 } else if false { 2 } else { 3 };\n}";
+    assert!(errors(src).is_empty());
+    assert_eq!(ty(src, "a"), "i32");
+}
+
+#[test]
+fn branches_take_the_expected_type() {
+    let src = "fn f {\n\tlet a: u64 = if true { 1 } else { 2 };\n}";
+    assert!(errors(src).is_empty());
+    assert_eq!(ty(src, "a"), "u64");
+}
+
+#[test]
+fn unannotated_numbers_default_to_i32_and_f32() {
+    let src = "fn f {\n\tlet a = 1;\n\tlet b = 1.5;\n\tlet c: i64 = 1;\n\tlet d: f64 = 1.5;\n}";
+    assert!(errors(src).is_empty());
+    assert_eq!(ty(src, "a"), "i32");
+    assert_eq!(ty(src, "b"), "f32");
+    assert_eq!(ty(src, "c"), "i64");
+    assert_eq!(ty(src, "d"), "f64");
+}
+
+#[test]
+fn bindings_without_annotations_take_the_type_of_their_value() {
+    let src = "const a: u16 = 1;\nconst b = a;\nfn f {\n\tlet c = b;\n\tlet d: u8 = c;\n}";
+    assert_eq!(ty(src, "b"), "u16");
+    assert_eq!(errors(src), ["expected `u8`, found `u16`"]);
+}
+
+const NUM: &str = "type Num = i32;\ntype Other = i32;\nconst int: i32 = 0;\n";
+
+#[test]
+fn named_types_dont_unify_with_their_representation() {
+    let src = format!(
+        "{NUM}const num: Num = int;\nconst back: i32 = Num::{{int}};\nconst other: Other = \
+         Num::{{int}};"
+    );
+    assert_eq!(
+        errors(&src),
+        [
+            "expected `Num`, found `i32`",
+            "expected `i32`, found `Num`",
+            "expected `Other`, found `Num`",
+        ]
+    );
+}
+
+#[test]
+fn named_types_can_be_cast_to_and_from_their_representation() {
+    let src = format!("{NUM}const num = Num::{{int}};\nconst back = i32::{{num}};");
+    assert!(errors(&src).is_empty());
+    assert_eq!(ty(&src, "num"), "Num");
+    assert_eq!(ty(&src, "back"), "i32");
+}
+
+#[test]
+fn bad_casts_explain_the_representation() {
+    let src = format!("{NUM}const s = Num::{{\"s\"}};\nconst o = Other::{{Num::{{int}}}};");
+    let diags = diagnostics(&src);
+    assert_eq!(diags.len(), 2);
+
+    assert_eq!(
+        diags[0].message,
+        "cannot cast a value of type `str` to `Num`"
+    );
+    assert_eq!(&src[diags[0].span.as_range()], "{\"s\"}");
+    assert_eq!(&src[diags[0].labels[0].span.as_range()], "Num");
+    assert_eq!(diags[0].labels[0].message, "`Num` is represented as `i32`");
+
+    // named types with the same representation can only be cast through it
+    assert_eq!(
+        diags[1].message,
+        "cannot cast a value of type `Num` to `Other`"
+    );
+    let src = format!("{NUM}const o = Other::{{i32::{{Num::{{int}}}}}};");
+    assert!(errors(&src).is_empty());
+}
+
+const COORDS: &str = "type Coords = (lat: f64, lng: f64);
+type PubCoords = (pub lat: f64, pub lng: f64);
+type Origin = (lat: f64 = 0, lng: f64 = 0);
+";
+
+#[test]
+fn private_fields_cannot_be_constructed() {
+    let src = format!("{COORDS}const coords = Coords::(lat = 0, lng = 0);");
+    assert_eq!(
+        errors(&src),
+        ["cannot construct a type with private fields"]
+    );
+
+    let src = format!("{COORDS}const origin = Origin::(lat = 5);");
+    assert_eq!(errors(&src), ["`lat` is a private field"]);
+    assert!(errors(&format!("{COORDS}const origin = Origin::();")).is_empty());
+}
+
+#[test]
+fn let_bindings_are_not_mutable() {
+    let src = "fn f {\n\tlet c = (lat = 0, lng = 0);\n\tlet x = c.lng;\n\tc.lng = x;\n}";
+    assert_eq!(
+        errors(src),
+        ["cannot assign to a field of `c`, it is declared with `let`, use `var` to make it mutable"]
+    );
+}
+
+#[test]
+fn structs_with_the_same_fields_can_be_cast() {
+    let src = format!(
+        "{COORDS}const origin = Origin::();
+fn f {{
+\tlet inline_coords = (lat = 0, lng = 0);
+\tlet vis_coords = PubCoords::{{inline_coords}};
+\tlet swapped = PubCoords::{{(lng = 0.0, lat = 0)}};
+\tlet leaked_documents = PubCoords::{{origin}};
+}}"
+    );
+
+    assert!(errors(&src).is_empty());
+    assert_eq!(ty(&src, "inline_coords"), "(lat: i32, lng: i32)");
+    assert_eq!(ty(&src, "swapped"), "PubCoords");
+}
+
+#[test]
+fn casts_to_types_with_private_fields_are_rejected() {
+    let src =
+        format!("{COORDS}fn f {{\n\tlet c = (lat = 0, lng = 0);\n\tlet s = Coords::{{c}};\n}}");
+    assert_eq!(errors(&src), ["cannot cast to type with private fields"]);
+}
+
+#[test]
+fn free_fns_cannot_build_types_with_private_fields() {
+    let src = format!(
+        "{COORDS}fn f: () -> Coords {{
+\tCoords::(lat = 0, lng = 0)
+}}
+fn g: (lat: f64, lng: f64) -> Coords {{
+\tCoords::{{arg}}
+}}
+fn PubCoords::h: () -> Coords {{
+\tCoords::(lat = 0, lng = 0)
+}}"
+    );
+
+    assert_eq!(
+        errors(&src),
+        [
+            "cannot construct a type with private fields",
+            "cannot cast to type with private fields",
+            "cannot construct a type with private fields",
+        ]
+    );
+}
+
+#[test]
+fn methods_can_build_their_type() {
+    let src = format!(
+        "{COORDS}fn Coords::new: () -> Self {{
+\tCoords::(lat = 0, lng = 0)
+}}
+fn Coords::from: (lat: f64, lng: f64) -> Self {{
+\tSelf::{{arg}}
+}}"
+    );
+
+    assert!(errors(&src).is_empty());
+}
+
+#[test]
+fn only_untyped_numbers_take_the_type_of_the_cast() {
+    let src = "type Small = (pub a: u8);
+type Int = (pub a: i32);
+fn f {
+\tlet big = (a = 300);
+\tlet float = (a = 1.5);
+\tlet typed: (a: i32) = (a = 1);
+\tlet small = Small::{big};
+\tlet int = Int::{float};
+\tlet other = Small::{typed};
+}";
+
+    assert_eq!(
+        errors(src),
+        [
+            "cannot cast a value of type `(a: i32)` to `Small`",
+            "cannot cast a value of type `(a: f32)` to `Int`",
+            "cannot cast a value of type `(a: i32)` to `Small`",
+        ]
+    );
+}
+
+#[test]
+fn fields_of_inline_structs_in_named_types_are_private() {
+    let nested = "type NestedInline = (
+\tpub *,
+\televation: f64 = 0,
+\tcoords: (lat: f64 = 0, lng: f64 = 0),
+);
+";
+
+    let src = "type NestedInline = (pub *, coords: (pub lat: f64 = 0));";
+    assert_eq!(
+        errors(src),
+        ["fields of un-named structs are always public"]
+    );
+
+    let src =
+        format!("{nested}const n = NestedInline::(elevation = 0, coords = (lat = 0, lng = 0));");
+    assert_eq!(errors(&src), ["`lat` and `lng` are private fields"]);
+    assert!(errors(&format!("{nested}const n = NestedInline::(elevation = 0);")).is_empty());
+}
+
+const POINT: &str = "type Point = (pub:get x: i32, y: i32 = 0);\n";
+
+#[test]
+fn methods_can_write_read_only_fields() {
+    let src = format!(
+        "{POINT}fn Point::new: i32::(x) -> Self {{
+\tvar p = Self::(x = x, y = 1);
+\tp.x = 2;
+\tp
+}}
+fn Point::move: Self -> i32 -> Self {{
+\tvar p = self;
+\tp.x = arg;
+\tp
+}}"
+    );
+
+    assert!(errors(&src).is_empty());
+}
+
+#[test]
+fn methods_can_assign_to_fields_of_self() {
+    let src = format!(
+        "{POINT}fn Point::set: Self -> i32 -> Self {{
+\tself.x = arg;
+\tself.y = arg;
+\tself
+}}"
+    );
+
+    assert!(errors(&src).is_empty());
+}
+
+#[test]
+fn arguments_cannot_be_assigned() {
+    let src = "type Pair = (pub a: i32);
+fn Pair::reset: Self -> i32 -> Self {
+\tself = Pair::(a = arg);
+\targ = 0;
+\tself
+}
+fn set: Pair -> i32 -> Pair {
+\tself.a = arg;
+\tself
+}";
+
+    assert_eq!(
+        errors(src),
+        [
+            "cannot assign to `self`, it is an argument",
+            "cannot assign to `arg`, it is an argument",
+            "cannot assign to a field of `self`, it is an argument",
+        ]
+    );
+}
+
+#[test]
+fn free_fns_cannot_write_read_only_fields() {
+    let src = format!(
+        "{POINT}fn new: i32 -> Point {{
+\tPoint::(x = arg)
+}}
+fn move: Point -> i32 -> Point {{
+\tvar p = self;
+\tp.x = arg;
+\tp
+}}"
+    );
+
+    assert_eq!(
+        errors(&src),
+        [
+            "cannot construct a type with read-only fields",
+            "cannot assign to `x`, it is a read-only field of `Point`",
+        ]
+    );
+}
+
+#[test]
+fn methods_are_reached_through_their_type() {
+    let src = format!(
+        "{POINT}fn Point::new: i32 -> Self {{
+\tSelf::(x = arg)
+}}
+fn Point::get: Self -> () -> i32 {{
+\tself.x
+}}
+fn f {{
+\tlet p = 1 > Point::new;
+\tlet x = p > Point::get;
+}}"
+    );
+
+    assert!(errors(&src).is_empty());
+    assert_eq!(ty(&src, "p"), "Point");
+    assert_eq!(ty(&src, "x"), "() -> i32");
+}
+
+#[test]
+fn methods_must_be_declared_on_types() {
+    let src = format!(
+        "{POINT}fn Point::new {{}}
+fn Point::new {{}}
+fn i32::new {{}}
+fn Missing::new {{}}
+fn f {{
+\tlet g = Point::old;
+\tlet h = new;
+}}"
+    );
+
+    assert_eq!(
+        errors(&src),
+        [
+            "`Point` already has a method `new`",
+            "cannot declare methods on `i32`, it isn't declared with `type`",
+            "cannot find `Missing` in this scope",
+            "`Point` has no method `old`",
+            "cannot find `new` in this scope",
+        ]
+    );
+}
+
+#[test]
+fn read_only_fields_can_only_be_read_outside_of_methods() {
+    let src = format!(
+        "{POINT}fn make: i32 -> i32 {{
+\tvar p = Point::(x = 1);
+\tp.x = arg;
+\tp.x
+}}
+fn other: i32::(x) -> i32 {{
+\tlet p = Point::(x = x);
+\tp.x
+}}"
+    );
+
+    assert_eq!(
+        errors(&src),
+        [
+            "cannot construct a type with read-only fields",
+            "cannot assign to `x`, it is a read-only field of `Point`",
+            "cannot construct a type with read-only fields",
+        ]
+    );
+}
+
+#[test]
+fn vars_given_different_variants_are_unions() {
+    let src = "fn f {\n\tvar v = (a = 1);\n\tv = (b = \"s\");\n\tv = (a = 2);\n}";
+    assert!(errors(src).is_empty());
+    assert_eq!(ty(src, "v"), "union (a: i32, b: str)");
+}
+
+#[test]
+fn assignments_in_nested_blocks_are_variants() {
+    let src = "fn f {
+\tvar v = (a = 1);
+\tif true {
+\t\tv = (b = true);
+\t} else {
+\t\twhile false {
+\t\t\tv = (c = 'c');
+\t\t};
+\t};
+}";
+
+    assert!(errors(src).is_empty());
+    assert_eq!(ty(src, "v"), "union (a: i32, b: bool, c: char)");
+}
+
+#[test]
+fn vars_given_one_variant_are_structs() {
+    let src = "fn f {\n\tvar v = (a = 1);\n\tv = (a = 2);\n\tlet w = (a = 1);\n}";
+    assert!(errors(src).is_empty());
+    assert_eq!(ty(src, "v"), "(a: i32)");
+    assert_eq!(ty(src, "w"), "(a: i32)");
+}
+
+#[test]
+fn assignments_to_unions_are_checked() {
+    let src = "fn f {\n\tvar v = (a = 1);\n\tv = (b = 'c');\n\tv = (b = 2);\n\tv = 3;\n}";
+    assert_eq!(
+        errors(src),
+        [
+            "expected `char`, found `i32`",
+            "expected `union (a: i32, b: char)`, found `i32`",
+        ]
+    );
+}
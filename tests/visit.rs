@@ -0,0 +1,143 @@
+//! passes written as small overrides of `Visitor` and `VisitorMut`
+
+use rename_me::{
+    ast::{walk_expr_mut, Alias, BinOp, Block, Expr, IntLit, SourceFile, Visitor, VisitorMut},
+    parse_source,
+};
+
+fn parse(src: &str) -> SourceFile {
+    parse_source(src).expect("the source should parse")
+}
+
+/// every alias in the order it is visited
+#[derive(Default)]
+struct Aliases<'ast> {
+    aliases: Vec<&'ast Alias>,
+}
+
+impl<'ast> Visitor<'ast> for Aliases<'ast> {
+    fn visit_alias(&mut self, alias: &'ast Alias) {
+        self.aliases.push(alias);
+    }
+}
+
+fn aliases(file: &SourceFile) -> Vec<&str> {
+    let mut visitor = Aliases::default();
+    visitor.visit_source_file(file);
+    visitor
+        .aliases
+        .iter()
+        .map(|alias| alias.alias.as_str())
+        .collect()
+}
+
+/// every int literal in the order it is visited
+fn ints(file: &SourceFile) -> Vec<u64> {
+    struct Ints(Vec<u64>);
+
+    impl Visitor<'_> for Ints {
+        fn visit_int_lit(&mut self, lit: &IntLit) {
+            self.0.push(lit.val);
+        }
+    }
+
+    let mut visitor = Ints(Vec::new());
+    visitor.visit_source_file(file);
+    visitor.0
+}
+
+#[test]
+fn every_node_is_visited_in_source_order() {
+    let file = parse(
+        "const a: b = c;
+type T = (d: e = f);
+fn T::g: h -> i {
+\tj.k = l&;
+\tif m { n } else { o };
+\twhile p { return q; };
+\tlet r = $\"{s > t}\";
+\tu < v
+}
+iface W {
+\tconst x: y = z;
+\tfn aa { bb }
+}",
+    );
+
+    let expected: Vec<_> = "a b c T d e f T g h i j k l m n o p q r s t u v W x y z aa bb"
+        .split(' ')
+        .collect();
+    assert_eq!(aliases(&file), expected);
+}
+
+#[test]
+fn overrides_that_dont_walk_skip_the_children() {
+    struct Shallow<'ast>(Aliases<'ast>);
+
+    impl<'ast> Visitor<'ast> for Shallow<'ast> {
+        fn visit_alias(&mut self, alias: &'ast Alias) {
+            self.0.visit_alias(alias);
+        }
+
+        fn visit_block(&mut self, _block: &'ast Block) {}
+    }
+
+    let file = parse("fn f: a -> b { c }\nconst d = { e };");
+    let mut visitor = Shallow(Aliases::default());
+    visitor.visit_source_file(&file);
+
+    let names: Vec<_> = visitor.0.aliases.iter().map(|a| a.alias.as_str()).collect();
+    assert_eq!(names, ["f", "a", "b", "d"]);
+}
+
+#[test]
+fn mutable_visitors_rewrite_nodes_in_place() {
+    struct Rename;
+
+    impl VisitorMut for Rename {
+        fn visit_alias_mut(&mut self, alias: &mut Alias) {
+            alias.alias = alias.alias.to_uppercase();
+        }
+
+        fn visit_int_lit_mut(&mut self, lit: &mut IntLit) {
+            lit.val += 1;
+        }
+    }
+
+    let mut file = parse("fn f { let a = 1 > g; if a { b.c = 2; }; }");
+    Rename.visit_source_file_mut(&mut file);
+
+    assert_eq!(aliases(&file), ["F", "A", "G", "A", "B", "C"]);
+    assert_eq!(ints(&file), [2, 3]);
+}
+
+#[test]
+fn mutable_visitors_can_desugar_exprs() {
+    /// turns `f < x` into `x > f`
+    struct FlipPipes;
+
+    impl VisitorMut for FlipPipes {
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            if let Expr::BinaryExpr {
+                lhs,
+                rhs,
+                op: op @ BinOp::PipeL,
+                ..
+            } = expr
+            {
+                std::mem::swap(lhs, rhs);
+                *op = BinOp::PipeR;
+            }
+
+            walk_expr_mut(self, expr);
+        }
+    }
+
+    let original = parse("const a = f < g < x;");
+    let mut file = original.clone();
+    FlipPipes.visit_source_file_mut(&mut file);
+
+    assert_eq!(aliases(&file), ["a", "x", "g", "f"]);
+    // the rewrite doesn't leak into the shared subtrees of the original
+    assert_eq!(aliases(&original), ["a", "f", "g", "x"]);
+}